@@ -0,0 +1,56 @@
+/// Hundredths in a beat per minute.
+pub const BPM_SCALE: u32 = 100;
+
+/// Tempo in hundredths of a beat per minute, so 122.5 BPM is
+/// `Bpm::from_hundredths(12_250)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bpm(u32);
+
+impl Default for Bpm {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+impl From<u32> for Bpm {
+    fn from(bpm: u32) -> Self {
+        Self::new(bpm)
+    }
+}
+
+impl Bpm {
+    /// Fastest tempo derived from a measurement, such as tap tempo.
+    pub const MAX: Self = Self::new(999);
+    /// Slowest tempo derived from a measurement, such as tap tempo.
+    pub const MIN: Self = Self::new(1);
+
+    pub const fn new(bpm: u32) -> Self {
+        Self(bpm * BPM_SCALE)
+    }
+
+    pub const fn from_hundredths(hundredths: u32) -> Self {
+        Self(hundredths)
+    }
+
+    pub fn hundredths(&self) -> u32 {
+        self.0
+    }
+
+    /// Nearest whole BPM.
+    pub fn whole(&self) -> u32 {
+        (self.0 + BPM_SCALE / 2) / BPM_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rounds_to_whole_bpm() {
+        assert_eq!(122, Bpm::from_hundredths(12_249).whole());
+        assert_eq!(123, Bpm::from_hundredths(12_250).whole());
+        assert_eq!(Bpm::from_hundredths(9_000), Bpm::from(90));
+    }
+}
@@ -0,0 +1,189 @@
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClockSource {
+    Internal,
+    External(Ppqn),
+}
+
+/// Pulses per quarter note of an external clock. Zero is treated as one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ppqn(pub u32);
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Clock {
+    source: ClockSource,
+    last_edge_micros: Option<u64>,
+    pulse: Option<u32>,
+    held: u32,
+    dropped: bool,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(ClockSource::Internal)
+    }
+}
+
+impl Clock {
+    pub fn new(source: ClockSource) -> Self {
+        let source = match source {
+            ClockSource::External(Ppqn(0)) => ClockSource::External(Ppqn(1)),
+            source => source,
+        };
+
+        Self {
+            source,
+            last_edge_micros: Option::None,
+            pulse: Option::None,
+            held: 0,
+            dropped: false,
+        }
+    }
+
+    pub fn source(&self) -> ClockSource {
+        self.source
+    }
+
+    pub fn ppqn(&self) -> Option<Ppqn> {
+        match self.source {
+            ClockSource::Internal => Option::None,
+            ClockSource::External(ppqn) => Option::Some(ppqn),
+        }
+    }
+
+    pub fn ticks_per_pulse(&self, resolution: u32) -> u32 {
+        self.ppqn().map_or(1, |ppqn| (resolution / ppqn.0).max(1))
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.dropped
+    }
+
+    /// Tick count of the last received pulse, if the external clock has
+    /// started.
+    pub fn pulse_count(&self, resolution: u32) -> Option<u32> {
+        self.pulse
+            .map(|pulse| pulse * self.ticks_per_pulse(resolution))
+    }
+
+    /// Forgets any received pulses so the next edge is treated as the first.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.source);
+    }
+
+    /// Whether the tick at `count` must wait for the next external pulse.
+    ///
+    /// Ticks free-run at the estimated tempo up to the next pulse boundary
+    /// and hold there. If the pulse is more than a whole pulse late the
+    /// clock is considered dropped and ticks free-run until it resumes.
+    pub fn hold(&mut self, count: u32, resolution: u32) -> bool {
+        let ticks_per_pulse = self.ticks_per_pulse(resolution);
+
+        match (self.source, self.pulse) {
+            (ClockSource::Internal, _) => false,
+            (ClockSource::External(_), Option::None) => true,
+            (ClockSource::External(_), Option::Some(_)) if self.dropped => false,
+            (ClockSource::External(_), Option::Some(pulse)) => {
+                if count < (pulse + 1) * ticks_per_pulse {
+                    return false
+                }
+
+                self.held += 1;
+                if self.held > ticks_per_pulse {
+                    self.dropped = true;
+                }
+
+                !self.dropped
+            }
+        }
+    }
+
+    /// Registers an external clock edge received at `micros`.
+    ///
+    /// Returns the interval to the previous edge when it can be used to
+    /// estimate tempo, i.e. the clock was already running and did not drop
+    /// out in between.
+    pub fn edge(&mut self, micros: u64, count: u32, resolution: u32) -> Option<u64> {
+        let ticks_per_pulse = self.ticks_per_pulse(resolution);
+        let interval = match (self.last_edge_micros, self.dropped) {
+            (Option::Some(last), false) if micros > last => Option::Some(micros - last),
+            _ => Option::None,
+        };
+
+        self.pulse = Option::Some(match self.pulse {
+            Option::Some(pulse) if !self.dropped => pulse + 1,
+            _ => (count + ticks_per_pulse / 2) / ticks_per_pulse,
+        });
+        self.last_edge_micros = Option::Some(micros);
+        self.held = 0;
+        self.dropped = false;
+
+        interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_treats_zero_ppqn_as_one() {
+        let clock = Clock::new(ClockSource::External(Ppqn(0)));
+
+        assert_eq!(ClockSource::External(Ppqn(1)), clock.source());
+        assert_eq!(1_920, clock.ticks_per_pulse(1_920));
+    }
+
+    #[test]
+    fn it_never_holds_on_internal_clock() {
+        let mut clock = Clock::new(ClockSource::Internal);
+
+        assert!(!clock.hold(0, 1_920));
+        assert!(!clock.hold(1_920, 1_920));
+    }
+
+    #[test]
+    fn it_holds_until_the_first_pulse() {
+        let mut clock = Clock::new(ClockSource::External(Ppqn(24)));
+
+        assert!(clock.hold(0, 1_920));
+        assert_eq!(Option::None, clock.edge(0, 0, 1_920));
+        assert!(!clock.hold(0, 1_920));
+        assert!(!clock.hold(79, 1_920));
+        assert!(clock.hold(80, 1_920));
+    }
+
+    #[test]
+    fn it_measures_the_interval_between_pulses() {
+        let mut clock = Clock::new(ClockSource::External(Ppqn(24)));
+
+        assert_eq!(Option::None, clock.edge(1_000, 0, 1_920));
+        assert_eq!(Option::Some(20_833), clock.edge(21_833, 80, 1_920));
+        assert_eq!(Option::Some(80), clock.pulse_count(1_920));
+    }
+
+    #[test]
+    fn it_drops_out_after_a_missing_pulse() {
+        let mut clock = Clock::new(ClockSource::External(Ppqn(24)));
+        clock.edge(0, 0, 1_920);
+
+        for _ in 0..80 {
+            assert!(clock.hold(80, 1_920));
+        }
+
+        assert!(!clock.hold(80, 1_920));
+        assert!(clock.is_dropped());
+    }
+
+    #[test]
+    fn it_realigns_to_the_nearest_pulse_on_resume() {
+        let mut clock = Clock::new(ClockSource::External(Ppqn(24)));
+        clock.edge(0, 0, 1_920);
+        for _ in 0..81 {
+            clock.hold(80, 1_920);
+        }
+
+        assert_eq!(Option::None, clock.edge(1_000_000, 430, 1_920));
+        assert_eq!(Option::Some(400), clock.pulse_count(1_920));
+        assert!(!clock.is_dropped());
+    }
+}
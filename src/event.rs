@@ -0,0 +1,14 @@
+use heapless::Vec;
+
+use crate::output::MAX_OUTPUTS;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event<const OUTPUTS: usize = MAX_OUTPUTS> {
+    /// Ticks to skip before the tick on which the event happens, so zero
+    /// means the next call to `Seq::tick`.
+    pub ticks: u32,
+    /// Indexes of the outputs whose `on` changes on that tick. Empty when
+    /// nothing changes within the scheduling horizon or the tick only starts
+    /// a loop.
+    pub outputs: Vec<usize, OUTPUTS>,
+}
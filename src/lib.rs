@@ -1,12 +1,36 @@
 #![cfg_attr(not(test), no_std)]
 
+pub use bpm::{Bpm, BPM_SCALE};
+pub use clock::{ClockSource, Ppqn};
 pub use output::{
-    euclid, Config as OutputConfig, Density, Frac, Length, OutputType, Prob, Pwm, Rate,
+    euclid, euclid_n, turing, Accent, AccentN, Config as OutputConfig, ConfigN as OutputConfigN,
+    Density, Envelope, EnvelopeTime, Frac, Length, Logic, LogicOp, Note, OutputType, Prob, Pwm,
+    Quantizer, Ratchet, Rate, RateAlign, Rotation, Scale, Sequence, SequenceN, Step, Swing,
+    Waveform, MAX_OUTPUTS, MAX_REGISTER_BITS, MAX_STEPS,
 };
 
-pub use crate::seq::Seq;
+pub use crate::{
+    event::Event,
+    midi::{MidiMessage, MidiNote, MidiParser, MIDI_PPQN},
+    preset::{
+        preset_len, Preset, PresetBank, PresetError, Recall, SeqPreset, SeqPresetN, BEATS_PER_BAR,
+        PRESET_VERSION,
+    },
+    seq::{Seq, SeqN},
+    song::{Song, SongEntry, SongN, SongPosition, MAX_SONG_ENTRIES},
+    tap_tempo::{TapTempo, TapTempoN, MAX_TAPS, TAP_TIMEOUT_MICROS},
+    transport::{Transport, TransportEvent},
+};
 
+mod bpm;
+mod clock;
+mod event;
 mod math;
+mod midi;
 mod output;
+mod preset;
 mod seq;
+mod song;
+mod tap_tempo;
 mod tick;
+mod transport;
@@ -0,0 +1,23 @@
+pub(crate) use self::out::MidiOut;
+pub use self::{
+    out::MidiNote,
+    parser::{MidiMessage, MidiParser},
+};
+
+mod out;
+mod parser;
+
+/// MIDI clock is fixed at 24 pulses per quarter note.
+pub const MIDI_PPQN: u32 = 24;
+
+/// Song position is counted in MIDI beats, i.e. sixteenth notes.
+pub(crate) const MIDI_BEATS_PER_QUARTER: u32 = 4;
+const MAX_SONG_POSITION: u32 = 0x3FFF;
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const CLOCK: u8 = 0xF8;
+const START: u8 = 0xFA;
+const CONTINUE: u8 = 0xFB;
+const STOP: u8 = 0xFC;
+const SONG_POSITION: u8 = 0xF2;
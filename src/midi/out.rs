@@ -0,0 +1,216 @@
+use heapless::Deque;
+
+use super::*;
+use crate::transport::{Transport, TransportEvent};
+
+const MIDI_OUT_LEN: usize = 32;
+
+/// Maps the gate of an output to a MIDI note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiNote {
+    pub channel: u8,
+    pub note: u8,
+    pub velocity: u8,
+    /// Velocity of accented hits.
+    pub accent_velocity: u8,
+}
+
+/// Queue of MIDI bytes for the firmware to push to a UART.
+///
+/// Messages that don't fit are dropped whole, so it should be drained at
+/// least once per tick.
+#[derive(Default)]
+pub(crate) struct MidiOut {
+    bytes: Deque<u8, MIDI_OUT_LEN>,
+    clock_enabled: bool,
+}
+
+impl MidiOut {
+    pub fn is_clock_enabled(&self) -> bool {
+        self.clock_enabled
+    }
+
+    pub fn set_clock_enabled(&mut self, enabled: bool) {
+        self.clock_enabled = enabled;
+    }
+
+    pub fn pop(&mut self) -> Option<u8> {
+        self.bytes.pop_front()
+    }
+
+    /// Ticks per MIDI clock pulse.
+    pub fn ticks_per_clock(resolution: u32) -> u32 {
+        (resolution / MIDI_PPQN).max(1)
+    }
+
+    /// Queues a clock pulse if the tick at `count` falls on one.
+    pub fn clock(&mut self, count: u32, resolution: u32) {
+        if self.clock_enabled && count % Self::ticks_per_clock(resolution) == 0 {
+            self.push(&[CLOCK]);
+        }
+    }
+
+    /// Queues the messages announcing a transport event that took effect
+    /// with the transport now in `transport` at tick `count`.
+    pub fn transport(
+        &mut self,
+        event: TransportEvent,
+        transport: Transport,
+        count: u32,
+        resolution: u32,
+    ) {
+        if !self.clock_enabled {
+            return
+        }
+
+        match event {
+            TransportEvent::Start => self.push(&[START]),
+            TransportEvent::Stop | TransportEvent::Pause => self.push(&[STOP]),
+            TransportEvent::Continue => {
+                self.song_position(count, resolution);
+                self.push(&[CONTINUE]);
+            }
+            TransportEvent::Reset if transport.is_running() => self.push(&[START]),
+            TransportEvent::Reset => self.song_position(0, resolution),
+        }
+    }
+
+    /// Queues a Note On, or a Note Off when the gate went low.
+    pub fn note(&mut self, note: &MidiNote, on: bool, accent: bool) {
+        let channel = note.channel & 0x0F;
+        let velocity = match (on, accent) {
+            (false, _) => 0,
+            (true, false) => note.velocity,
+            (true, true) => note.accent_velocity,
+        };
+        let status = if on { NOTE_ON } else { NOTE_OFF };
+
+        self.push(&[status | channel, note.note & 0x7F, velocity & 0x7F]);
+    }
+
+    fn song_position(&mut self, count: u32, resolution: u32) {
+        let ticks_per_beat = (resolution / MIDI_BEATS_PER_QUARTER).max(1);
+        let position = (count / ticks_per_beat).min(MAX_SONG_POSITION);
+
+        self.push(&[
+            SONG_POSITION,
+            (position & 0x7F) as u8,
+            (position >> 7) as u8,
+        ]);
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        if self.bytes.capacity() - self.bytes.len() < bytes.len() {
+            return
+        }
+
+        for byte in bytes {
+            self.bytes.push_back(*byte).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(out: &mut MidiOut) -> Vec<u8> {
+        core::iter::from_fn(|| out.pop()).collect()
+    }
+
+    fn enabled() -> MidiOut {
+        let mut out: MidiOut = Default::default();
+        out.set_clock_enabled(true);
+        out
+    }
+
+    #[test]
+    fn it_pulses_at_24_ppqn() {
+        let mut out = enabled();
+
+        for count in 0..1_920 {
+            out.clock(count, 1_920);
+        }
+
+        assert_eq!(vec![CLOCK; 24], drain(&mut out));
+    }
+
+    #[test]
+    fn it_is_silent_when_disabled() {
+        let mut out: MidiOut = Default::default();
+
+        out.clock(0, 1_920);
+        out.transport(TransportEvent::Start, Transport::Running, 0, 1_920);
+
+        assert!(drain(&mut out).is_empty());
+    }
+
+    #[test]
+    fn it_sends_notes() {
+        let mut out: MidiOut = Default::default();
+        let note = MidiNote {
+            channel: 9,
+            note: 36,
+            velocity: 100,
+            accent_velocity: 127,
+        };
+
+        out.note(&note, true, false);
+        out.note(&note, false, false);
+        out.note(&note, true, true);
+
+        assert_eq!(
+            vec![0x99, 36, 100, 0x89, 36, 0, 0x99, 36, 127],
+            drain(&mut out)
+        );
+    }
+
+    #[test]
+    fn it_drops_a_message_that_does_not_fit_whole() {
+        let mut out = enabled();
+        let note = MidiNote {
+            channel: 0,
+            note: 36,
+            velocity: 100,
+            accent_velocity: 127,
+        };
+
+        for count in 0..(MIDI_OUT_LEN as u32 - 1) {
+            out.clock(count * 80, 1_920);
+        }
+        out.note(&note, true, false);
+        assert_eq!(vec![CLOCK; MIDI_OUT_LEN - 1], drain(&mut out));
+
+        out.note(&note, true, false);
+        assert_eq!(vec![0x90, 36, 100], drain(&mut out));
+    }
+
+    #[test]
+    fn it_sends_song_position_before_continue() {
+        let mut out = enabled();
+
+        out.transport(
+            TransportEvent::Continue,
+            Transport::Running,
+            1_920 * 40,
+            1_920,
+        );
+
+        assert_eq!(vec![SONG_POSITION, 0x20, 0x01, CONTINUE], drain(&mut out));
+    }
+
+    #[test]
+    fn it_sends_transport_messages() {
+        let mut out = enabled();
+
+        out.transport(TransportEvent::Start, Transport::Running, 0, 1_920);
+        out.transport(TransportEvent::Pause, Transport::Paused, 10, 1_920);
+        out.transport(TransportEvent::Reset, Transport::Paused, 0, 1_920);
+        out.transport(TransportEvent::Reset, Transport::Running, 0, 1_920);
+
+        assert_eq!(
+            vec![START, STOP, SONG_POSITION, 0, 0, START],
+            drain(&mut out)
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use super::*;
+
+/// System real-time and song position messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MidiMessage {
+    Clock,
+    Start,
+    Continue,
+    Stop,
+    /// Position in MIDI beats, i.e. sixteenth notes.
+    SongPosition(u16),
+}
+
+/// Incremental MIDI byte stream parser.
+///
+/// Real-time bytes are recognised anywhere in the stream, even between the
+/// data bytes of another message. Channel messages, including those sent
+/// with running status, and other system messages are consumed and
+/// ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MidiParser {
+    data: [u8; 2],
+    received: usize,
+    status: Option<u8>,
+}
+
+impl MidiParser {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn parse(&mut self, byte: u8) -> Option<MidiMessage> {
+        match byte {
+            CLOCK => Option::Some(MidiMessage::Clock),
+            START => Option::Some(MidiMessage::Start),
+            CONTINUE => Option::Some(MidiMessage::Continue),
+            STOP => Option::Some(MidiMessage::Stop),
+            0xF9 | 0xFD..=0xFF => Option::None,
+            0x80..=0xF7 => {
+                self.status = Option::Some(byte);
+                self.received = 0;
+                Option::None
+            }
+            _ => self.data(byte),
+        }
+    }
+
+    fn data(&mut self, byte: u8) -> Option<MidiMessage> {
+        let status = self.status?;
+        let expected = data_len(status);
+        if self.received >= expected {
+            return Option::None
+        }
+
+        self.data[self.received] = byte;
+        self.received += 1;
+        if self.received < expected {
+            return Option::None
+        }
+
+        match status {
+            SONG_POSITION => {
+                self.status = Option::None;
+                let position = self.data[0] as u16 | ((self.data[1] as u16) << 7);
+                Option::Some(MidiMessage::SongPosition(position))
+            }
+            // Running status only applies to channel messages.
+            0x80..=0xEF => {
+                self.received = 0;
+                Option::None
+            }
+            _ => {
+                self.status = Option::None;
+                Option::None
+            }
+        }
+    }
+}
+
+/// Number of data bytes following `status`. Any further data bytes, such
+/// as the body of a system exclusive message, are ignored.
+fn data_len(status: u8) -> usize {
+    match status {
+        0xC0..=0xDF | 0xF1 | 0xF3 => 1,
+        0x80..=0xEF | SONG_POSITION => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> Vec<MidiMessage> {
+        let mut parser = MidiParser::new();
+        bytes
+            .iter()
+            .filter_map(|byte| parser.parse(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_real_time_messages() {
+        assert_eq!(
+            vec![
+                MidiMessage::Start,
+                MidiMessage::Clock,
+                MidiMessage::Stop,
+                MidiMessage::Continue
+            ],
+            parse(&[START, CLOCK, 0xFE, STOP, CONTINUE])
+        );
+    }
+
+    #[test]
+    fn it_parses_song_position() {
+        assert_eq!(
+            vec![MidiMessage::SongPosition(160)],
+            parse(&[SONG_POSITION, 0x20, 0x01])
+        );
+    }
+
+    #[test]
+    fn it_parses_real_time_messages_between_data_bytes() {
+        assert_eq!(
+            vec![MidiMessage::Clock, MidiMessage::SongPosition(160)],
+            parse(&[SONG_POSITION, 0x20, CLOCK, 0x01])
+        );
+    }
+
+    #[test]
+    fn it_ignores_channel_messages_with_running_status() {
+        assert_eq!(
+            vec![MidiMessage::Clock, MidiMessage::SongPosition(1)],
+            parse(&[
+                0x90,
+                0x3C,
+                0x64,
+                0x3E,
+                CLOCK,
+                0x64,
+                0x40,
+                SONG_POSITION,
+                0x01,
+                0x00
+            ])
+        );
+    }
+
+    #[test]
+    fn it_ignores_data_after_song_position() {
+        assert_eq!(
+            vec![MidiMessage::SongPosition(0)],
+            parse(&[SONG_POSITION, 0x00, 0x00, 0x00, 0x00])
+        );
+    }
+
+    #[test]
+    fn it_skips_system_exclusive() {
+        assert_eq!(
+            vec![MidiMessage::Clock, MidiMessage::Start],
+            parse(&[0xF0, 0x7E, 0x00, CLOCK, 0x06, 0x01, 0xF7, START, 0x01])
+        );
+    }
+}
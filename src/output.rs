@@ -1,9 +1,12 @@
 pub use self::{
     components::{
-        euclid, Density, Frac, Length, OutputType, Prob, Pwm, Rate, Rng, Sequence, MAX_STEPS,
+        euclid, euclid_n, turing, Accent, AccentN, Density, Envelope, EnvelopeState, EnvelopeTime,
+        Frac, Length, Logic, LogicOp, Note, OutputType, Prob, Pwm, Quantizer, Ratchet, Rate,
+        RateAlign, Rng, Rotation, Scale, Sequence, SequenceN, Step, Swing, Waveform,
+        MAX_REGISTER_BITS, MAX_STEPS, PHASE_CYCLE, RNG_SEED,
     },
-    config::Config,
-    output_state::{OutputState, OutputStates},
+    config::{Config, ConfigN},
+    output_state::{OutputState, OutputStatesN, MAX_OUTPUTS},
 };
 use super::tick::{Tick, RESOLUTION};
 
@@ -11,29 +14,54 @@ mod components;
 mod config;
 mod output_state;
 
-#[derive(Debug, PartialEq)]
-pub struct Output {
-    config: Config,
+/// An output of the default size, see `OutputN`.
+#[cfg(test)]
+pub type Output = OutputN<MAX_STEPS>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputN<const STEPS: usize> {
+    accent_index: Option<usize>,
+    config: ConfigN<STEPS>,
     cycle_target: u32,
     index: Option<usize>,
     off_target: u32,
+    /// Gate as the last `tick` left it.
+    on: bool,
+    /// Count from which steps are laid out.
+    origin: u32,
+    /// Ticks taken by `period_steps` steps.
+    period: u64,
+    period_steps: u32,
     resolution: u32,
+    /// Step, counted from the origin, that `index` started on. `None` once
+    /// the origin moves, when the step on the origin takes the next index.
+    step: Option<u64>,
+    step_on: bool,
+    swing_delay: u32,
 }
 
-impl Default for Output {
+impl<const STEPS: usize> Default for OutputN<STEPS> {
     fn default() -> Self {
         Self::new(RESOLUTION, &Tick::new(120), Default::default())
     }
 }
 
-impl Output {
-    pub fn new(resolution: u32, tick: &Tick, config: Config) -> Self {
+impl<const STEPS: usize> OutputN<STEPS> {
+    pub fn new(resolution: u32, tick: &Tick, config: ConfigN<STEPS>) -> Self {
         let mut output = Self {
+            accent_index: Option::None,
             config,
             cycle_target: 0,
             index: Option::None,
             off_target: 0,
+            on: false,
+            origin: 0,
+            period: 0,
+            period_steps: 1,
             resolution,
+            step: Option::None,
+            step_on: false,
+            swing_delay: 0,
         };
 
         output.set_output_type(tick, output.config.output_type());
@@ -41,31 +69,73 @@ impl Output {
         output
     }
 
+    pub fn config(&self) -> &ConfigN<STEPS> {
+        &self.config
+    }
+
+    /// Replaces the whole config while keeping the position in the
+    /// sequence.
+    pub fn set_config(&mut self, tick: &Tick, config: ConfigN<STEPS>) {
+        if self.config.accent() != config.accent() {
+            self.accent_index = Option::None;
+        }
+        self.config = config;
+        self.calc_targets(tick);
+    }
+
     fn calc_targets(&mut self, tick: &Tick) {
         self.calc_cycle_target();
         self.calc_off_target(tick);
+        self.calc_swing_delay();
     }
 
     fn calc_cycle_target(&mut self) {
-        self.cycle_target = (Into::<f32>::into(self.config.rate()) * self.resolution as f32) as u32
+        (self.period, self.period_steps) = self.config.rate().step_ticks(self.resolution);
+        self.cycle_target = (self.period / self.period_steps as u64) as u32
     }
 
     fn calc_off_target(&mut self, tick: &Tick) {
         self.off_target = match self.config.output_type() {
-            OutputType::Gate => self.config.pwm().off_target(tick, self.cycle_target),
+            OutputType::Gate
+            | OutputType::Pitch
+            | OutputType::Lfo
+            | OutputType::Envelope
+            | OutputType::Turing
+            | OutputType::Logic => self.config.pwm().off_target(tick, self.cycle_target),
             OutputType::Euclid => Pwm::Pew.off_target(tick, self.cycle_target),
         }
     }
 
+    fn calc_swing_delay(&mut self) {
+        self.swing_delay = match self.config.output_type() {
+            OutputType::Lfo => 0,
+            _ => self.config.swing().delay(self.cycle_target),
+        }
+    }
+
     pub fn set_sequence(&mut self, length: Length, density: Density) {
         self.config.set_sequence(length, density);
     }
 
+    /// The accent starts over from its first step on the next step.
+    pub fn set_accent(&mut self, length: Length, density: Density, rotation: Rotation) {
+        self.config.set_accent(length, density, rotation);
+        self.accent_index = Option::None;
+    }
+
     pub fn set_output_type(&mut self, tick: &Tick, output_type: OutputType) {
         self.config.set_output_type(output_type);
         self.calc_targets(tick);
     }
 
+    pub fn set_envelope(&mut self, envelope: Envelope) {
+        self.config.set_envelope(envelope);
+    }
+
+    pub fn set_logic(&mut self, logic: Logic) {
+        self.config.set_logic(logic);
+    }
+
     pub fn set_prob(&mut self, prob: Prob) {
         self.config.set_prob(prob);
     }
@@ -80,19 +150,108 @@ impl Output {
         self.calc_targets(tick);
     }
 
+    /// Changes the rate and lays out steps from `origin`, so a step starts
+    /// on it.
+    pub fn align_rate(&mut self, tick: &Tick, rate: Rate, origin: u32) {
+        self.origin = origin;
+        self.step = Option::None;
+        self.set_rate(tick, rate);
+    }
+
+    pub fn set_ratchet(&mut self, step: usize, ratchet: Ratchet) {
+        self.config.set_ratchet(step, ratchet);
+    }
+
+    pub fn set_note(&mut self, step: usize, note: Note) {
+        self.config.set_note(step, note);
+    }
+
+    pub fn set_tie(&mut self, step: usize, tie: bool) {
+        self.config.set_tie(step, tie);
+    }
+
+    pub fn set_quantizer(&mut self, quantizer: Quantizer) {
+        self.config.set_quantizer(quantizer);
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.config.set_rotation(rotation);
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.config.set_waveform(waveform);
+    }
+
+    pub fn set_swing(&mut self, tick: &Tick, swing: Swing) {
+        self.config.set_swing(swing);
+        self.calc_targets(tick);
+    }
+
+    pub fn set_tick(&mut self, tick: &Tick) {
+        self.calc_targets(tick);
+    }
+
+    /// Rewinds to before the first step.
+    pub fn reset(&mut self) {
+        self.accent_index = Option::None;
+        self.index = Option::None;
+        self.step = Option::None;
+    }
+
+    /// Logic outputs don't play their own sequence, their gate is set from
+    /// their inputs by `Seq`.
     pub fn tick(&mut self, count: u32, state: &mut OutputState) {
+        if self.config.output_type() == OutputType::Logic {
+            return
+        }
+
         let initial_on = state.on;
         let initial_index = state.index;
 
-        if self.is_cycle_starting(count) {
+        let (step, start, len) = self.window(count);
+        let phase = count - start;
+
+        if self.is_cycle_starting(phase) {
             state.index = self.calc_index().unwrap();
+            self.step = Option::Some(step);
+            if state.index == 0 {
+                state.rng.restart_loop();
+                state.loop_register = state.register;
+            }
             state.on = self.is_on(state);
-        } else if self.is_cycle_finished(count) {
-            state.on = false;
+            if state.on {
+                let note = self.config.sequence()[state.index].note;
+                state.note = self.config.quantizer().quantize(note);
+            }
+            state.accent = self.is_accented(state);
+            if self.config.output_type() == OutputType::Lfo {
+                state.sample = state.rng.rand_u16();
+            }
+            self.step_on = state.on;
+        } else {
+            let (sub_phase, sub_len, sub_end) = self.ratchet(phase, len);
+
+            if self.is_cycle_starting(sub_phase) {
+                state.on = self.step_on;
+                state.accent = self.step_on && self.is_accented(state);
+            } else if self.is_cycle_finished(sub_phase, sub_len) && !self.is_tied(sub_end, len) {
+                state.on = false;
+                state.accent = false;
+            }
         }
 
+        self.on = state.on;
         state.on_change = initial_on != state.on;
         state.index_change = initial_index != state.index;
+
+        if self.config.output_type() == OutputType::Envelope && state.on_change {
+            if state.on {
+                let level = self.value(count, state);
+                state.envelope.trigger(count, level);
+            } else {
+                state.envelope.release(count);
+            }
+        }
     }
 
     #[inline(always)]
@@ -100,22 +259,207 @@ impl Output {
         self.index = self.index.map_or(Option::Some(0), |index| {
             Option::Some((index + 1) % self.config.length().0 as usize)
         });
+        self.accent_index = self.accent_index.map_or(Option::Some(0), |index| {
+            Option::Some((index + 1) % self.config.accent().length().0 as usize)
+        });
         self.index
     }
 
+    /// Step, start and length of the step window `count` falls in. Step
+    /// `n` starts `n * period / period_steps` ticks after the origin, so
+    /// steps that don't fit the tick grid evenly never drift. Swing delays
+    /// the steps at odd indexes of the sequence and lengthens the steps
+    /// before them, so with an odd length the last step isn't swung and
+    /// every loop swings the same steps.
     #[inline(always)]
-    fn is_cycle_starting(&self, count: u32) -> bool {
-        count % self.cycle_target == 0
+    fn window(&self, count: u32) -> (u64, u32, u32) {
+        let offset = (count - self.origin) as u64;
+        let steps = self.period_steps as u64;
+        let step = ((offset + 1) * steps - 1) / self.period;
+        let start = |step: u64| {
+            let delay = if self.is_swung(step) {
+                self.swing_delay
+            } else {
+                0
+            };
+            self.origin + (step * self.period / steps) as u32 + delay
+        };
+
+        let step = if count < start(step) { step - 1 } else { step };
+        let step_start = start(step);
+
+        (step, step_start, start(step + 1) - step_start)
+    }
+
+    /// Whether the step `step` steps from the origin plays an odd index.
+    /// Steps on the origin and before the first step are never swung.
+    #[inline(always)]
+    fn is_swung(&self, step: u64) -> bool {
+        let length = self.config.length().0 as u64;
+        let index = match (self.index, self.step) {
+            (Option::None, _) => return false,
+            _ if step == 0 => return false,
+            (Option::Some(index), Option::Some(current)) if step == current => {
+                return index % 2 == 1
+            }
+            (Option::Some(index), Option::Some(current)) => {
+                index as u64 + step.saturating_sub(current) % length
+            }
+            (Option::Some(index), Option::None) => index as u64 + 1 + step % length,
+        };
+
+        index % length % 2 == 1
+    }
+
+    /// Value of the output after the tick at `count` on the full scale of a
+    /// `u16`. LFOs complete one cycle per step and the end of the gate, set
+    /// by `Pwm`, skews the waveform to peak there. Envelopes are triggered
+    /// by each hit and released when its gate falls. Turing Machines step
+    /// through the eight newest bits of their register. Other outputs are at
+    /// full scale while their gate is on.
+    pub fn value(&self, count: u32, state: &OutputState) -> u16 {
+        match self.config.output_type() {
+            OutputType::Lfo => {}
+            OutputType::Envelope => {
+                return self
+                    .config
+                    .envelope()
+                    .level(&state.envelope, count, self.cycle_target)
+            }
+            OutputType::Turing => return (state.register & 0xFF) as u16 * 0x101,
+            _ => return if state.on { u16::MAX } else { 0 },
+        }
+
+        let (_, start, len) = self.window(count);
+        let phase = (count - start) as u64;
+        let len = len as u64;
+        let half = (PHASE_CYCLE / 2) as u64;
+        let skew = (self.scaled_off_target(len as u32) as u64).clamp(1, len.max(2) - 1);
+
+        let phase = if phase < skew {
+            phase * half / skew
+        } else {
+            half + (phase - skew) * half / (len - skew).max(1)
+        };
+
+        self.config.waveform().value(phase as u32, state.sample)
+    }
+
+    /// The first count at or after `count` that starts a step.
+    pub fn next_step(&self, count: u32) -> u32 {
+        let (_, start, len) = self.window(count);
+
+        if count == start {
+            count
+        } else {
+            start + len
+        }
+    }
+
+    /// Whether the tick at `count` starts the sequence from its first step.
+    pub fn is_loop_starting(&self, count: u32) -> bool {
+        let (_, start, _) = self.window(count);
+        let length = self.config.length().0 as usize;
+
+        count == start && self.index.map_or(true, |index| (index + 1) % length == 0)
+    }
+
+    /// The first count at or after `count` on which `tick` may change the
+    /// state. Every count in between is a no-op, so ticking only on edges
+    /// is bit-exact with ticking on every count. A gate still high past its
+    /// off target, after the target shrank, falls on the next multiple of
+    /// it. Logic outputs never change on their own.
+    pub fn next_edge(&self, count: u32) -> u32 {
+        if self.config.output_type() == OutputType::Logic {
+            return u32::MAX
+        }
+
+        let (_, start, len) = self.window(count);
+        let phase = count - start;
+        let (sub_phase, sub_len, sub_end) = self.ratchet(phase, len);
+        let off_target = self.scaled_off_target(sub_len);
+        let sub_start = phase - sub_phase;
+
+        let next = if sub_phase == 0 {
+            phase
+        } else if sub_phase <= off_target || self.on {
+            (sub_start + sub_phase.next_multiple_of(off_target)).min(sub_end)
+        } else {
+            sub_end
+        };
+
+        start + next
+    }
+
+    /// Phase, length and end phase of the ratchet sub-gate `phase` falls in
+    /// within a step window `len` ticks long. The last sub-gate absorbs any
+    /// remainder. A window shorter than the ratchet fires one sub-gate per
+    /// tick.
+    #[inline(always)]
+    fn ratchet(&self, phase: u32, len: u32) -> (u32, u32, u32) {
+        let repeats: u32 = self
+            .index
+            .and_then(|index| self.config.sequence().get(index))
+            .map_or(1, |step| step.ratchet.into())
+            .min(len.max(1));
+        let sub_len = len / repeats;
+        let sub = (phase / sub_len).min(repeats - 1);
+        let sub_end = if sub == repeats - 1 {
+            len
+        } else {
+            (sub + 1) * sub_len
+        };
+
+        (phase - sub * sub_len, sub_len, sub_end)
+    }
+
+    #[inline(always)]
+    fn is_cycle_starting(&self, phase: u32) -> bool {
+        phase == 0
     }
 
     #[inline(always)]
     fn is_on(&self, state: &mut OutputState) -> bool {
-        state.rng.rand_bool(self.config.prob()) && self.config.sequence()[state.index]
+        if self.config.output_type() == OutputType::Turing {
+            let flip = !state.rng.is_locked() && state.rng.rand_bool(self.config.prob());
+            state.register = turing(state.register, self.config.length(), flip);
+            return state.register & 1 == 1
+        }
+
+        state.rng.rand_bool(self.config.prob()) && self.config.sequence()[state.index].on
     }
 
+    /// Only hits are accented, so the accent layer intersects the sequence.
     #[inline(always)]
-    fn is_cycle_finished(&self, count: u32) -> bool {
-        count % self.cycle_target % self.off_target == 0
+    fn is_accented(&self, state: &OutputState) -> bool {
+        state.on
+            && self
+                .accent_index
+                .is_some_and(|index| self.config.accent().is_accented(index))
+    }
+
+    /// Whether the gate of a sub-gate ending at `sub_end` is held into the
+    /// next step.
+    #[inline(always)]
+    fn is_tied(&self, sub_end: u32, len: u32) -> bool {
+        sub_end == len
+            && self
+                .index
+                .and_then(|index| self.config.sequence().get(index))
+                .is_some_and(|step| step.tie)
+    }
+
+    /// The off target scales with the window so swung steps and ratchet
+    /// sub-gates keep their pulse width.
+    #[inline(always)]
+    fn is_cycle_finished(&self, phase: u32, len: u32) -> bool {
+        phase % self.scaled_off_target(len) == 0
+    }
+
+    #[inline(always)]
+    fn scaled_off_target(&self, len: u32) -> u32 {
+        let off_target = (self.off_target as u64 * len as u64 / self.cycle_target as u64) as u32;
+        off_target.max(1)
     }
 }
 
@@ -132,11 +476,19 @@ mod tests {
         let output = Output::new(1_920, &Tick::new(120), config.clone());
 
         let expected = Output {
+            accent_index: Option::None,
             config,
             cycle_target: 1_920,
             index: Option::None,
             off_target: 960,
+            on: false,
+            origin: 0,
+            period: 1_920,
+            period_steps: 1,
             resolution: 1_920,
+            step: Option::None,
+            step_on: false,
+            swing_delay: 0,
         };
 
         assert_eq!(expected, output);
@@ -284,6 +636,444 @@ mod tests {
         assert_eq!(ON, state.on);
     }
 
+    #[test]
+    fn it_delays_even_numbered_steps_at_swing_p75() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_swing(&tick, Swing::P75);
+
+        assert_eq!(960, output.swing_delay);
+
+        output.tick(0, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(0, state.index);
+
+        output.tick(1_439, &mut state);
+        assert_eq!(ON, state.on);
+
+        output.tick(1_440, &mut state);
+        assert_eq!(OFF, state.on);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(OFF, state.on);
+        assert_eq!(0, state.index);
+
+        output.tick(2_880, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(1, state.index);
+
+        output.tick(3_359, &mut state);
+        assert_eq!(ON, state.on);
+
+        output.tick(3_360, &mut state);
+        assert_eq!(OFF, state.on);
+
+        output.tick(3_840, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(2, state.index);
+    }
+
+    #[test]
+    fn it_swings_the_same_steps_every_loop_of_an_odd_length() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_sequence(Length(3), Density(3));
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_swing(&tick, Swing::P75);
+
+        let mut starts = Vec::new();
+        for count in 0..1_920 * 9 {
+            output.tick(count, &mut state);
+            if state.index_change || count == 0 {
+                starts.push((state.index, count % (1_920 * 3)));
+            }
+        }
+
+        assert_eq!(9, starts.len());
+        for starts in starts.chunks(3) {
+            assert_eq!(&[(0, 0), (1, 2_880), (2, 3_840)], starts);
+        }
+    }
+
+    #[test]
+    fn it_updates_on_change_at_swing_p66() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_swing(&tick, Swing::P66);
+
+        output.tick(0, &mut state);
+        assert_eq!(ON, state.on_change);
+
+        output.tick(1_267, &mut state);
+        assert_eq!(ON, state.on_change);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(OFF, state.on_change);
+        assert_eq!(OFF, state.index_change);
+
+        output.tick(2_534, &mut state);
+        assert_eq!(ON, state.on_change);
+        assert_eq!(ON, state.index_change);
+    }
+
+    #[test]
+    fn it_fires_sub_gates_for_a_ratcheted_step() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_ratchet(0, Ratchet::R3);
+
+        output.tick(0, &mut state);
+        assert_eq!(ON, state.on);
+        output.tick(319, &mut state);
+        assert_eq!(ON, state.on);
+        output.tick(320, &mut state);
+        assert_eq!(OFF, state.on);
+        assert_eq!(ON, state.on_change);
+
+        output.tick(640, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(ON, state.on_change);
+        assert_eq!(OFF, state.index_change);
+        output.tick(960, &mut state);
+        assert_eq!(OFF, state.on);
+
+        output.tick(1_280, &mut state);
+        assert_eq!(ON, state.on);
+        output.tick(1_600, &mut state);
+        assert_eq!(OFF, state.on);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(1, state.index);
+        output.tick(2_880, &mut state);
+        assert_eq!(OFF, state.on);
+    }
+
+    #[test]
+    fn it_fires_one_sub_gate_per_tick_when_the_step_is_shorter_than_the_ratchet() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_rate(Rate::Ratio(1_000, 1));
+        config.set_ratchet(0, Ratchet::R4);
+        let mut output = Output::new(1_000, &tick, config);
+
+        for count in 0..4 {
+            assert_eq!(count, output.next_edge(count));
+            output.tick(count, &mut state);
+            assert_eq!(ON, state.on);
+        }
+    }
+
+    #[test]
+    fn it_plays_the_note_of_each_step() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Pitch);
+        config.set_note(1, Note::new(3, 7));
+        let mut output = Output::new(1_920, &tick, config);
+
+        output.tick(0, &mut state);
+        assert_eq!(Note::new(0, 0), state.note);
+        output.tick(960, &mut state);
+        assert_eq!(OFF, state.on);
+        assert_eq!(Note::new(0, 0), state.note);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(Note::new(3, 7), state.note);
+    }
+
+    #[test]
+    fn it_holds_the_gate_of_a_tied_step() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Pitch);
+        config.set_tie(0, true);
+        config.set_ratchet(0, Ratchet::R2);
+        let mut output = Output::new(1_920, &tick, config);
+
+        output.tick(0, &mut state);
+        output.tick(480, &mut state);
+        assert_eq!(OFF, state.on);
+        output.tick(960, &mut state);
+        assert_eq!(ON, state.on);
+        output.tick(1_440, &mut state);
+        assert_eq!(ON, state.on);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(OFF, state.on_change);
+        assert_eq!(ON, state.index_change);
+        output.tick(2_880, &mut state);
+        assert_eq!(OFF, state.on);
+    }
+
+    #[test]
+    fn it_cycles_an_lfo_once_per_step() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Lfo);
+        config.set_waveform(Waveform::Triangle);
+        let mut output = Output::new(1_920, &tick, config);
+
+        output.tick(0, &mut state);
+        assert_eq!(0, output.value(0, &state));
+        assert_eq!(32_768, output.value(480, &state));
+        assert_eq!(u16::MAX, output.value(960, &state));
+        assert_eq!(0, output.value(1_920, &state));
+    }
+
+    #[test]
+    fn it_skews_an_lfo_by_pwm() {
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Lfo);
+        config.set_waveform(Waveform::Triangle);
+        config.set_pwm(Pwm::P20);
+        let output = Output::new(1_920, &tick, config);
+        let state = Default::default();
+
+        assert_eq!(u16::MAX, output.value(384, &state));
+        assert_eq!(32_768, output.value(1_152, &state));
+    }
+
+    #[test]
+    fn it_samples_a_new_value_each_lfo_cycle() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Lfo);
+        config.set_waveform(Waveform::SampleAndHold);
+        let mut output = Output::new(1_920, &tick, config);
+
+        output.tick(0, &mut state);
+        let first = output.value(0, &state);
+        assert_eq!(first, output.value(1_919, &state));
+
+        output.tick(1_920, &mut state);
+        assert_ne!(first, output.value(1_920, &state));
+    }
+
+    #[test]
+    fn it_skips_sub_gates_when_a_ratcheted_step_is_off() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_output_type(&tick, OutputType::Euclid);
+        output.set_ratchet(1, Ratchet::R2);
+
+        output.tick(0, &mut state);
+        output.tick(39, &mut state);
+        assert_eq!(OFF, state.on);
+
+        output.tick(1_920, &mut state);
+        assert_eq!(OFF, state.on);
+        output.tick(2_880, &mut state);
+        assert_eq!(OFF, state.on);
+    }
+
+    #[test]
+    fn it_accents_hits_of_the_sequence() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        output.set_output_type(&tick, OutputType::Euclid);
+        output.set_accent(Length(3), Density(1), Rotation(0));
+
+        output.tick(0, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(ON, state.accent);
+        output.tick(39, &mut state);
+        assert_eq!(OFF, state.accent);
+
+        for step in 1..4 {
+            output.tick(1_920 * step, &mut state);
+            assert_eq!(OFF, state.on);
+            assert_eq!(OFF, state.accent);
+        }
+
+        output.tick(1_920 * 4, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(OFF, state.accent);
+
+        for step in 5..12 {
+            output.tick(1_920 * step, &mut state);
+        }
+
+        output.tick(1_920 * 12, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(ON, state.accent);
+    }
+
+    #[test]
+    fn it_restarts_the_accent_when_it_changes_mid_step() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut config = Config::new();
+        config.set_sequence(Length(16), Density(16));
+        config.set_ratchet(5, Ratchet::R2);
+        config.set_accent(Length(16), Density(16), Rotation(0));
+        let mut output = Output::new(1_920, &tick, config);
+
+        for step in 0..6 {
+            output.tick(1_920 * step, &mut state);
+        }
+        assert_eq!(ON, state.accent);
+
+        output.set_accent(Length(2), Density(1), Rotation(0));
+        output.tick(1_920 * 5 + 960, &mut state);
+        assert_eq!(ON, state.on);
+        assert_eq!(OFF, state.accent);
+
+        output.tick(1_920 * 6, &mut state);
+        assert_eq!(ON, state.accent);
+        output.tick(1_920 * 7, &mut state);
+        assert_eq!(OFF, state.accent);
+    }
+
+    #[test]
+    fn it_knows_when_the_loop_starts() {
+        let tick = Tick::new(120);
+        let mut config: Config = Default::default();
+        config.set_sequence(Length(2), Density(2));
+        let mut output = Output::new(1_920, &tick, config);
+        let mut state: OutputState = Default::default();
+
+        assert!(output.is_loop_starting(0));
+        output.tick(0, &mut state);
+        assert!(!output.is_loop_starting(1));
+        assert!(!output.is_loop_starting(1_920));
+        for count in 1..3_840 {
+            output.tick(count, &mut state);
+        }
+        assert!(output.is_loop_starting(3_840));
+    }
+
+    #[test]
+    fn it_finds_the_next_edge() {
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        let mut state: OutputState = Default::default();
+
+        assert_eq!(0, output.next_edge(0));
+
+        output.tick(0, &mut state);
+        assert_eq!(960, output.next_edge(1));
+        assert_eq!(960, output.next_edge(960));
+        assert_eq!(1_920, output.next_edge(961));
+    }
+
+    #[test]
+    fn it_lays_out_steps_from_an_aligned_rate_change() {
+        let tick = Tick::new(120);
+        let mut output = Output::new(1_920, &tick, Config::new());
+
+        assert_eq!(1_920, output.next_step(100));
+        assert_eq!(1_920, output.next_step(1_920));
+
+        output.align_rate(&tick, Rate::Div(3, Frac::Zero), 7_680);
+        assert_eq!(7_680, output.next_step(7_680));
+        assert_eq!(13_440, output.next_step(7_681));
+        assert_eq!(13_440, output.next_edge(11_000));
+    }
+
+    #[test]
+    fn it_lays_out_every_ratio_exactly() {
+        let tick = Tick::new(120);
+
+        for num in 1..=32 {
+            for den in 1..=16 {
+                let mut config = Config::new();
+                config.set_rate(Rate::Ratio(num, den));
+                let output = Output::new(RESOLUTION, &tick, config);
+                let period = den * RESOLUTION;
+                let (short, long) = (period / num, period.div_ceil(num));
+
+                let mut start = output.next_step(0);
+                for step in 1..=2 * num {
+                    let next = output.next_step(start + 1);
+                    assert_eq!(step * period / num, next, "{num}:{den} step {step}");
+                    assert!(next - start == short || next - start == long);
+                    start = next;
+                }
+                assert_eq!(2 * period, start);
+            }
+        }
+    }
+
+    #[test]
+    fn it_keeps_swung_ratios_on_the_pair_grid() {
+        let tick = Tick::new(120);
+
+        for num in 1..=16 {
+            for den in 1..=8 {
+                let mut config = Config::new();
+                config.set_rate(Rate::Ratio(num, den));
+                let mut output = Output::new(RESOLUTION, &tick, config);
+                output.set_swing(&tick, Swing::P66);
+                let period = den * RESOLUTION;
+
+                for pair in 0..num {
+                    let start = 2 * pair * period / num;
+                    assert_eq!(start, output.next_step(start));
+                    assert_eq!(start, output.next_step(start.saturating_sub(1)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_does_not_drift_over_long_runs() {
+        let tick = Tick::new(120);
+
+        for (num, den) in [(3, 2), (5, 4), (7, 8), (1, 3), (11, 7)] {
+            let mut config = Config::new();
+            config.set_rate(Rate::Ratio(num, den));
+            let output = Output::new(RESOLUTION, &tick, config);
+            let period = den * RESOLUTION;
+            let periods = (u32::MAX / 2) / period;
+
+            let count = periods * period;
+            assert_eq!(count, output.next_step(count));
+            assert_eq!(count, output.next_step(count - period / num + 1));
+            assert_eq!(count + period / num, output.next_step(count + 1));
+        }
+    }
+
+    #[test]
+    fn it_finds_the_next_edge_of_a_swung_ratcheted_step() {
+        let tick = Tick::new(120);
+        let config = Config::new();
+        let mut output = Output::new(1_920, &tick, config);
+        let mut state: OutputState = Default::default();
+        output.set_swing(&tick, Swing::P75);
+        output.set_ratchet(1, Ratchet::R2);
+
+        output.tick(0, &mut state);
+        assert_eq!(1_440, output.next_edge(1));
+        assert_eq!(2_880, output.next_edge(1_441));
+
+        output.tick(2_880, &mut state);
+        assert_eq!(3_120, output.next_edge(2_881));
+        assert_eq!(3_360, output.next_edge(3_121));
+        assert_eq!(3_600, output.next_edge(3_361));
+        assert_eq!(3_840, output.next_edge(3_601));
+    }
+
     #[test]
     fn it_skips_cycles_based_on_prob() {
         let mut state: OutputState = Default::default();
@@ -308,6 +1098,29 @@ mod tests {
         assert_eq!(OFF, state.on);
     }
 
+    #[test]
+    fn it_replays_prob_decisions_while_random_is_locked() {
+        let mut state: OutputState = Default::default();
+        let tick = Tick::new(120);
+        let mut output = Output::new(1_920, &tick, Config::new());
+        output.set_prob(Prob::P50);
+        output.set_sequence(Length(8), Density(8));
+        state.rng.set_locked(true);
+
+        let mut loops: [[bool; 8]; 3] = Default::default();
+        for (n, decisions) in loops.iter_mut().enumerate() {
+            for (step, decision) in decisions.iter_mut().enumerate() {
+                output.tick(1_920 * (n * 8 + step) as u32, &mut state);
+                *decision = state.on;
+            }
+        }
+
+        assert_eq!(loops[0], loops[1]);
+        assert_eq!(loops[1], loops[2]);
+        assert!(loops[0].contains(&ON));
+        assert!(loops[0].contains(&OFF));
+    }
+
     #[test]
     fn it_works_with_pwm_pew() {
         let mut state: OutputState = Default::default();
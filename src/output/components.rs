@@ -1,19 +1,45 @@
+pub use accent::{Accent, AccentN};
 pub use density::Density;
-pub use euclid::{euclid, Sequence, MAX_STEPS};
+pub use envelope::{Envelope, EnvelopeState, EnvelopeTime};
+pub use euclid::{euclid, euclid_n, Sequence, SequenceN, MAX_STEPS};
 pub use frac::Frac;
 pub use length::Length;
+pub use logic::{Logic, LogicOp};
+pub use note::{Note, MILLIVOLTS_PER_OCTAVE, SEMITONES_PER_OCTAVE};
 pub use output_type::OutputType;
 pub use prob::Prob;
 pub use pwm::Pwm;
+pub use quantizer::Quantizer;
+pub use ratchet::Ratchet;
 pub use rate::Rate;
-pub use rng::Rng;
+pub use rate_align::RateAlign;
+pub use rng::{Rng, RNG_SEED};
+pub use rotation::Rotation;
+pub use scale::Scale;
+pub use step::Step;
+pub use swing::Swing;
+pub use turing::{turing, MAX_REGISTER_BITS};
+pub use waveform::{Waveform, PHASE_CYCLE};
 
+mod accent;
 mod density;
+mod envelope;
 mod euclid;
 mod frac;
 mod length;
+mod logic;
+mod note;
 mod output_type;
 mod prob;
 mod pwm;
+mod quantizer;
+mod ratchet;
 mod rate;
+mod rate_align;
 mod rng;
+mod rotation;
+mod scale;
+mod step;
+mod swing;
+mod turing;
+mod waveform;
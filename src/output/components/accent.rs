@@ -0,0 +1,95 @@
+use heapless::Vec;
+
+use super::{euclid_n, Density, Length, Rotation, SequenceN, MAX_STEPS};
+
+pub type Accent = AccentN<MAX_STEPS>;
+
+/// A second Euclidean pattern that marks some hits of the main sequence as
+/// accented. It has its own length so it can run polymetric to the main
+/// sequence.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccentN<const STEPS: usize> {
+    density: Density,
+    length: Length,
+    rotation: Rotation,
+    sequence: SequenceN<STEPS>,
+}
+
+impl<const STEPS: usize> Default for AccentN<STEPS> {
+    fn default() -> Self {
+        let mut accent = Self {
+            density: Density(0),
+            length: Length(STEPS as u32),
+            rotation: Rotation(0),
+            sequence: Vec::new(),
+        };
+
+        accent.set(accent.length, accent.density, accent.rotation);
+
+        accent
+    }
+}
+
+impl<const STEPS: usize> AccentN<STEPS> {
+    pub fn density(&self) -> Density {
+        self.density
+    }
+
+    pub fn length(&self) -> Length {
+        self.length
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    pub fn is_accented(&self, index: usize) -> bool {
+        self.sequence.get(index).is_some_and(|step| step.on)
+    }
+
+    /// The length is kept between 1 and `STEPS` and the density at most the
+    /// length.
+    pub fn set(&mut self, length: Length, density: Density, rotation: Rotation) {
+        self.length = Length(length.0.clamp(1, STEPS as u32));
+        self.density = Density(density.0.min(self.length.0));
+        self.rotation = rotation;
+        euclid_n(self.density, self.length, self.rotation, &mut self.sequence);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_accents_nothing_by_default() {
+        let accent: Accent = Default::default();
+
+        assert!((0..MAX_STEPS).all(|index| !accent.is_accented(index)));
+    }
+
+    #[test]
+    fn it_accents_a_euclidean_pattern_of_length_three_at_density_one() {
+        let mut accent: Accent = Default::default();
+        accent.set(Length(3), Density(1), Rotation(0));
+
+        assert!(accent.is_accented(0));
+        assert!(!accent.is_accented(1));
+        assert!(!accent.is_accented(2));
+        assert!(!accent.is_accented(3));
+    }
+
+    #[test]
+    fn it_keeps_the_length_within_bounds() {
+        let mut accent: Accent = Default::default();
+        accent.set(Length(0), Density(4), Rotation(0));
+
+        assert_eq!(Length(1), accent.length());
+        assert_eq!(Density(1), accent.density());
+        assert!(accent.is_accented(0));
+
+        accent.set(Length(MAX_STEPS as u32 + 1), Density(1), Rotation(0));
+        assert_eq!(Length(MAX_STEPS as u32), accent.length());
+    }
+}
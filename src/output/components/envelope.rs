@@ -0,0 +1,205 @@
+const PERCENT: u32 = 100;
+const LEVEL_MAX: u32 = u16::MAX as u32;
+
+/// Length of an envelope stage, either fixed or scaling with the length of
+/// a step so it follows `Rate` and bpm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EnvelopeTime {
+    Ticks(u32),
+    StepPercent(u32),
+}
+
+impl EnvelopeTime {
+    pub fn ticks(&self, step_len: u32) -> u32 {
+        match self {
+            EnvelopeTime::Ticks(ticks) => *ticks,
+            EnvelopeTime::StepPercent(percent) => {
+                (step_len as u64 * *percent as u64 / PERCENT as u64) as u32
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Envelope {
+    /// Rises and falls on every hit regardless of the length of the gate.
+    Ad {
+        attack: EnvelopeTime,
+        decay: EnvelopeTime,
+    },
+    /// Holds at `sustain` while the gate is on and releases when it falls.
+    Adsr {
+        attack: EnvelopeTime,
+        decay: EnvelopeTime,
+        sustain: u16,
+        release: EnvelopeTime,
+    },
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope::Ad {
+            attack: EnvelopeTime::Ticks(0),
+            decay: EnvelopeTime::StepPercent(50),
+        }
+    }
+}
+
+/// Where the envelope of an output was last triggered and released.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnvelopeState {
+    /// Tick and level the attack started from.
+    trigger: Option<(u32, u16)>,
+    release: Option<u32>,
+}
+
+impl EnvelopeState {
+    pub fn trigger(&mut self, count: u32, level: u16) {
+        self.trigger = Option::Some((count, level));
+        self.release = Option::None;
+    }
+
+    pub fn release(&mut self, count: u32) {
+        if self.trigger.is_some() {
+            self.release = Option::Some(count);
+        }
+    }
+}
+
+impl Envelope {
+    /// Level at `count` on the full scale of a `u16`.
+    pub fn level(&self, state: &EnvelopeState, count: u32, step_len: u32) -> u16 {
+        let (trigger, from) = match state.trigger {
+            Option::Some(trigger) if trigger.0 <= count => trigger,
+            _ => return 0,
+        };
+
+        let level = match (self, state.release) {
+            (Envelope::Adsr { release: time, .. }, Option::Some(release))
+                if (trigger..=count).contains(&release) =>
+            {
+                let released = self.curve(release - trigger, from, step_len);
+                fall(released, 0, count - release, time.ticks(step_len))
+            }
+            _ => self.curve(count - trigger, from, step_len),
+        };
+
+        level as u16
+    }
+
+    /// Level `ticks` into the attack, decay and sustain stages.
+    fn curve(&self, ticks: u32, from: u16, step_len: u32) -> u32 {
+        let (attack, decay, sustain) = match self {
+            Envelope::Ad { attack, decay } => (attack, decay, 0),
+            Envelope::Adsr {
+                attack,
+                decay,
+                sustain,
+                ..
+            } => (attack, decay, *sustain as u32),
+        };
+        let attack = attack.ticks(step_len);
+        let decay = decay.ticks(step_len);
+
+        if ticks < attack {
+            let from = from as u32;
+            from + ((LEVEL_MAX - from) as u64 * ticks as u64 / attack as u64) as u32
+        } else {
+            fall(LEVEL_MAX, sustain, ticks - attack, decay)
+        }
+    }
+}
+
+/// Linear fall from `from` to `to` over `len` ticks.
+fn fall(from: u32, to: u32, ticks: u32, len: u32) -> u32 {
+    if ticks >= len {
+        return to
+    }
+
+    from - ((from - to) as u64 * ticks as u64 / len as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADSR: Envelope = Envelope::Adsr {
+        attack: EnvelopeTime::Ticks(100),
+        decay: EnvelopeTime::Ticks(100),
+        sustain: 32_768,
+        release: EnvelopeTime::StepPercent(50),
+    };
+
+    #[test]
+    fn it_scales_times_with_the_step() {
+        assert_eq!(10, EnvelopeTime::Ticks(10).ticks(1_920));
+        assert_eq!(480, EnvelopeTime::StepPercent(25).ticks(1_920));
+        assert_eq!(3_840, EnvelopeTime::StepPercent(200).ticks(1_920));
+    }
+
+    #[test]
+    fn it_is_silent_until_triggered() {
+        assert_eq!(0, ADSR.level(&Default::default(), 100, 1_920));
+    }
+
+    #[test]
+    fn it_rises_and_falls_on_an_ad_envelope() {
+        let envelope = Envelope::Ad {
+            attack: EnvelopeTime::Ticks(10),
+            decay: EnvelopeTime::StepPercent(10),
+        };
+        let mut state: EnvelopeState = Default::default();
+        state.trigger(100, 0);
+        state.release(101);
+
+        assert_eq!(0, envelope.level(&state, 100, 1_920));
+        assert_eq!(32_767, envelope.level(&state, 105, 1_920));
+        assert_eq!(u16::MAX, envelope.level(&state, 110, 1_920));
+        assert_eq!(32_768, envelope.level(&state, 206, 1_920));
+        assert_eq!(0, envelope.level(&state, 302, 1_920));
+    }
+
+    #[test]
+    fn it_sustains_until_released() {
+        let mut state: EnvelopeState = Default::default();
+        state.trigger(0, 0);
+
+        assert_eq!(u16::MAX, ADSR.level(&state, 100, 1_920));
+        assert_eq!(32_768, ADSR.level(&state, 200, 1_920));
+        assert_eq!(32_768, ADSR.level(&state, 5_000, 1_920));
+
+        state.release(5_000);
+        assert_eq!(16_384, ADSR.level(&state, 5_480, 1_920));
+        assert_eq!(0, ADSR.level(&state, 5_960, 1_920));
+    }
+
+    #[test]
+    fn it_releases_from_the_level_reached() {
+        let mut state: EnvelopeState = Default::default();
+        state.trigger(0, 0);
+        state.release(50);
+
+        assert_eq!(16_384, ADSR.level(&state, 530, 1_920));
+    }
+
+    #[test]
+    fn it_ignores_a_release_before_the_trigger() {
+        let state = EnvelopeState {
+            trigger: Option::Some((100, 0)),
+            release: Option::Some(50),
+        };
+
+        assert_eq!(32_768, ADSR.level(&state, 300, 1_920));
+    }
+
+    #[test]
+    fn it_attacks_from_the_level_it_was_retriggered_at() {
+        let mut state: EnvelopeState = Default::default();
+        state.trigger(0, 32_768);
+
+        assert_eq!(32_768, ADSR.level(&state, 0, 1_920));
+        assert_eq!(49_151, ADSR.level(&state, 50, 1_920));
+    }
+}
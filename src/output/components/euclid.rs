@@ -1,16 +1,31 @@
 use heapless::Vec;
 
-use super::{Density, Length};
+use super::{Density, Length, Rotation, Step};
 
 pub const MAX_STEPS: usize = 16;
 
-pub type Sequence = Vec<bool, MAX_STEPS>;
+pub type Sequence = SequenceN<MAX_STEPS>;
+pub type SequenceN<const STEPS: usize> = Vec<Step, STEPS>;
 
 // https://github.com/brianhouse/bjorklund (MIT)
-pub fn euclid(d: Density, l: Length, sequence: &mut Sequence) {
+//
+// The pattern starts on its first hit and is then rotated right by `r` steps.
+// Only the `on` of each step is written so per step settings such as
+// ratchets survive a change of density, length or rotation.
+pub fn euclid(d: Density, l: Length, r: Rotation, sequence: &mut Sequence) {
+    euclid_n(d, l, r, sequence)
+}
+
+/// `euclid` for a sequence of any size.
+pub fn euclid_n<const STEPS: usize>(
+    d: Density,
+    l: Length,
+    r: Rotation,
+    sequence: &mut SequenceN<STEPS>,
+) {
     sequence.resize_default(l.0 as usize).unwrap();
     if d.0 == 0 {
-        *sequence = sequence.iter_mut().map(|_| false).collect();
+        sequence.iter_mut().for_each(|step| step.on = false);
         return
     }
 
@@ -20,9 +35,9 @@ pub fn euclid(d: Density, l: Length, sequence: &mut Sequence) {
     assert!(sequence.len() == l.0 as usize);
     assert!(density <= length);
 
-    let mut pattern = Vec::<bool, MAX_STEPS>::new();
-    let mut counts = Vec::<i32, MAX_STEPS>::new();
-    let mut remainders = Vec::<i32, MAX_STEPS>::new();
+    let mut pattern = Vec::<bool, STEPS>::new();
+    let mut counts = Vec::<i32, STEPS>::new();
+    let mut remainders = Vec::<i32, STEPS>::new();
     let mut divisor = length - density;
     remainders.push(density).ok();
     let mut level = 0;
@@ -42,14 +57,17 @@ pub fn euclid(d: Density, l: Length, sequence: &mut Sequence) {
     build(level as i32, &counts, &remainders, &mut pattern);
     let i = pattern.iter().position(|&elem| elem).unwrap_or(0);
     pattern.rotate_left(i);
+    pattern.rotate_right(r.0 as usize % l.0 as usize);
 
-    *sequence = pattern;
+    for (step, on) in sequence.iter_mut().zip(pattern) {
+        step.on = on;
+    }
 }
 
 // This generates incorrect sequences compared to
 // PAMs and Euclidean Circles V2
 #[allow(dead_code)]
-fn euclid_wrong(d: Density, l: Length, sequence: &mut Sequence) {
+fn euclid_wrong(d: Density, l: Length, sequence: &mut Vec<bool, MAX_STEPS>) {
     let density = d.0 as i32;
     let length = l.0 as i32;
 
@@ -75,11 +93,11 @@ fn euclid_wrong(d: Density, l: Length, sequence: &mut Sequence) {
     sequence.rotate_left(i);
 }
 
-fn build(
+fn build<const STEPS: usize>(
     level: i32,
-    counts: &Vec<i32, MAX_STEPS>,
-    remainders: &Vec<i32, MAX_STEPS>,
-    pattern: &mut Sequence,
+    counts: &Vec<i32, STEPS>,
+    remainders: &Vec<i32, STEPS>,
+    pattern: &mut Vec<bool, STEPS>,
 ) {
     if level == -1 {
         pattern.push(false).ok();
@@ -99,9 +117,20 @@ fn build(
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const ON: bool = true;
-    const OFF: bool = false;
+    use crate::output::{Note, Ratchet};
+
+    const ON: Step = Step {
+        on: true,
+        ratchet: Ratchet::R1,
+        note: Note::new(0, 0),
+        tie: false,
+    };
+    const OFF: Step = Step {
+        on: false,
+        ratchet: Ratchet::R1,
+        note: Note::new(0, 0),
+        tie: false,
+    };
 
     #[test]
     fn it_builds_a_sequence_of_length_sixteen_at_density_four() {
@@ -114,7 +143,7 @@ mod tests {
         .unwrap();
 
         let mut result = Vec::new();
-        euclid(density, length, &mut result);
+        euclid(density, length, Rotation(0), &mut result);
 
         assert_eq!(expected, result);
     }
@@ -130,7 +159,7 @@ mod tests {
         .unwrap();
 
         let mut result = Vec::new();
-        euclid(density, length, &mut result);
+        euclid(density, length, Rotation(0), &mut result);
 
         assert_eq!(expected, result);
     }
@@ -144,7 +173,73 @@ mod tests {
             Vec::from_slice(&[ON, OFF, ON, OFF, OFF, ON, OFF, ON, OFF, OFF]).unwrap();
 
         let mut result = Vec::new();
-        euclid(density, length, &mut result);
+        euclid(density, length, Rotation(0), &mut result);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_builds_a_sequence_of_length_sixteen_at_density_four_at_rotation_one() {
+        let density = Density(4);
+        let length = Length(16);
+        let rotation = Rotation(1);
+
+        let expected: Sequence = Vec::from_slice(&[
+            OFF, ON, OFF, OFF, OFF, ON, OFF, OFF, OFF, ON, OFF, OFF, OFF, ON, OFF, OFF,
+        ])
+        .unwrap();
+
+        let mut result = Vec::new();
+        euclid(density, length, rotation, &mut result);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_builds_a_sequence_of_length_sixteen_at_density_nine_at_rotation_three() {
+        let density = Density(9);
+        let length = Length(16);
+        let rotation = Rotation(3);
+
+        let expected: Sequence = Vec::from_slice(&[
+            ON, ON, OFF, ON, OFF, ON, OFF, ON, OFF, ON, ON, OFF, ON, OFF, ON, OFF,
+        ])
+        .unwrap();
+
+        let mut result = Vec::new();
+        euclid(density, length, rotation, &mut result);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_builds_a_sequence_of_length_ten_at_density_four_at_rotation_twelve() {
+        let density = Density(4);
+        let length = Length(10);
+        let rotation = Rotation(12);
+
+        let expected: Sequence =
+            Vec::from_slice(&[OFF, OFF, ON, OFF, ON, OFF, OFF, ON, OFF, ON]).unwrap();
+
+        let mut result = Vec::new();
+        euclid(density, length, rotation, &mut result);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_builds_a_sequence_of_length_thirty_two_at_density_five() {
+        let density = Density(5);
+        let length = Length(32);
+
+        let expected: SequenceN<32> = Vec::from_slice(&[
+            ON, OFF, OFF, OFF, OFF, OFF, ON, OFF, OFF, OFF, OFF, OFF, OFF, ON, OFF, OFF, OFF, OFF,
+            OFF, ON, OFF, OFF, OFF, OFF, OFF, OFF, ON, OFF, OFF, OFF, OFF, OFF,
+        ])
+        .unwrap();
+
+        let mut result: SequenceN<32> = Vec::new();
+        euclid_n(density, length, Rotation(0), &mut result);
 
         assert_eq!(expected, result);
     }
@@ -157,7 +252,7 @@ mod tests {
         let expected: Sequence = Vec::from_slice(&[OFF, OFF]).unwrap();
 
         let mut result = Vec::new();
-        euclid(density, length, &mut result);
+        euclid(density, length, Rotation(0), &mut result);
 
         assert_eq!(expected, result);
     }
@@ -170,9 +265,33 @@ mod tests {
         let expected: Sequence = Vec::from_slice(&[OFF, OFF]).unwrap();
 
         let mut result = Vec::new();
-        result.push(true).ok();
-        result.push(false).ok();
-        euclid(density, length, &mut result);
+        result.push(ON).ok();
+        result.push(OFF).ok();
+        euclid(density, length, Rotation(0), &mut result);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_keeps_ratchets_when_rebuilding_a_sequence() {
+        let ratchet = Step {
+            ratchet: Ratchet::R3,
+            ..OFF
+        };
+
+        let expected: Sequence = Vec::from_slice(&[
+            ON,
+            OFF,
+            Step {
+                ratchet: Ratchet::R3,
+                ..ON
+            },
+            OFF,
+        ])
+        .unwrap();
+
+        let mut result = Vec::from_slice(&[OFF, OFF, ratchet]).unwrap();
+        euclid(Density(2), Length(4), Rotation(0), &mut result);
 
         assert_eq!(expected, result);
     }
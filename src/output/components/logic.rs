@@ -0,0 +1,68 @@
+/// Boolean operator of a logic output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogicOp {
+    #[default]
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    /// Inverts the first input and ignores the second.
+    Not,
+}
+
+/// Combines the gates of two other outputs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Logic {
+    pub op: LogicOp,
+    /// Index of the first input output.
+    pub a: usize,
+    /// Index of the second input output.
+    pub b: usize,
+}
+
+impl Default for Logic {
+    fn default() -> Self {
+        Self::new(LogicOp::And, 0, 1)
+    }
+}
+
+impl Logic {
+    pub const fn new(op: LogicOp, a: usize, b: usize) -> Self {
+        Self { op, a, b }
+    }
+
+    pub fn eval(&self, a: bool, b: bool) -> bool {
+        match self.op {
+            LogicOp::And => a && b,
+            LogicOp::Or => a || b,
+            LogicOp::Xor => a != b,
+            LogicOp::Nand => !(a && b),
+            LogicOp::Nor => !(a || b),
+            LogicOp::Not => !a,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUTS: [(bool, bool); 4] = [(false, false), (false, true), (true, false), (true, true)];
+
+    fn table(op: LogicOp) -> [bool; 4] {
+        INPUTS.map(|(a, b)| Logic::new(op, 0, 1).eval(a, b))
+    }
+
+    #[test]
+    fn it_evaluates_each_operator() {
+        assert_eq!([false, false, false, true], table(LogicOp::And));
+        assert_eq!([false, true, true, true], table(LogicOp::Or));
+        assert_eq!([false, true, true, false], table(LogicOp::Xor));
+        assert_eq!([true, true, true, false], table(LogicOp::Nand));
+        assert_eq!([true, false, false, false], table(LogicOp::Nor));
+        assert_eq!([true, true, false, false], table(LogicOp::Not));
+    }
+}
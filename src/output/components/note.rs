@@ -0,0 +1,69 @@
+pub const SEMITONES_PER_OCTAVE: u32 = 12;
+pub const MILLIVOLTS_PER_OCTAVE: u32 = 1_000;
+
+/// Pitch of a step, from C of octave 0 at 0V on a 1V/oct scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    pub octave: u8,
+    pub semitone: u8,
+}
+
+impl Note {
+    /// Semitones of 12 or more carry into the octave.
+    pub const fn new(octave: u8, semitone: u8) -> Self {
+        Self::from_semitones(octave as u32 * SEMITONES_PER_OCTAVE + semitone as u32)
+    }
+
+    pub const fn from_semitones(semitones: u32) -> Self {
+        Self {
+            octave: (semitones / SEMITONES_PER_OCTAVE) as u8,
+            semitone: (semitones % SEMITONES_PER_OCTAVE) as u8,
+        }
+    }
+
+    pub fn semitones(&self) -> u32 {
+        self.octave as u32 * SEMITONES_PER_OCTAVE + self.semitone as u32
+    }
+
+    pub fn millivolts(&self) -> u32 {
+        self.dac_code(MILLIVOLTS_PER_OCTAVE)
+    }
+
+    /// Code for a DAC spanning `codes_per_volt` codes per volt, rounded to
+    /// the nearest code.
+    pub fn dac_code(&self, codes_per_volt: u32) -> u32 {
+        (self.semitones() * codes_per_volt + SEMITONES_PER_OCTAVE / 2) / SEMITONES_PER_OCTAVE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_carries_semitones_into_the_octave() {
+        assert_eq!(
+            Note {
+                octave: 3,
+                semitone: 2
+            },
+            Note::new(2, 14)
+        );
+    }
+
+    #[test]
+    fn it_converts_to_one_volt_per_octave() {
+        assert_eq!(0, Note::new(0, 0).millivolts());
+        assert_eq!(83, Note::new(0, 1).millivolts());
+        assert_eq!(2_583, Note::new(2, 7).millivolts());
+        assert_eq!(3_000, Note::new(3, 0).millivolts());
+    }
+
+    #[test]
+    fn it_converts_to_a_dac_code() {
+        // 12-bit DAC over 0-10V.
+        assert_eq!(1_227, Note::new(3, 0).dac_code(409));
+        assert_eq!(1_261, Note::new(3, 1).dac_code(409));
+    }
+}
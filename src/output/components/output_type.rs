@@ -1,5 +1,14 @@
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutputType {
     Gate,
     Euclid,
+    Pitch,
+    Lfo,
+    Envelope,
+    /// Gates and a stepped value from a shift register that randomly flips
+    /// with `Prob` and loops unchanged while random is locked.
+    Turing,
+    /// Gate combined from the gates of other outputs by `Logic`.
+    Logic,
 }
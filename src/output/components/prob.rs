@@ -1,6 +1,7 @@
 use oorandom::Rand32;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Prob {
     P10,
     P20,
@@ -5,6 +5,7 @@ use crate::{math, tick::Tick};
 const PEW_MODE_IN_MICRO_SECONDS: u64 = 10_000;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pwm {
     Pew,
     P10,
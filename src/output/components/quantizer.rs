@@ -0,0 +1,106 @@
+use super::{Note, Scale, MILLIVOLTS_PER_OCTAVE, SEMITONES_PER_OCTAVE};
+
+/// Snaps pitches to the nearest note of a scale starting on `root`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quantizer {
+    pub scale: Scale,
+    /// Semitone of the root note, 0 being C.
+    pub root: u8,
+}
+
+impl Quantizer {
+    pub fn new(scale: Scale, root: u8) -> Self {
+        Self {
+            scale,
+            root: root % SEMITONES_PER_OCTAVE as u8,
+        }
+    }
+
+    /// Nearest note of the scale, favouring the lower note when two are
+    /// equally near. Notes pass through unchanged if the scale is empty.
+    pub fn quantize(&self, note: Note) -> Note {
+        Note::from_semitones(self.quantize_semitones(note.semitones()))
+    }
+
+    /// Nearest note of the scale to a 1V/oct voltage.
+    pub fn quantize_millivolts(&self, millivolts: u32) -> Note {
+        let semitones =
+            (millivolts * SEMITONES_PER_OCTAVE + MILLIVOLTS_PER_OCTAVE / 2) / MILLIVOLTS_PER_OCTAVE;
+
+        Note::from_semitones(self.quantize_semitones(semitones))
+    }
+
+    pub fn quantize_semitones(&self, semitones: u32) -> u32 {
+        let degree = semitones + SEMITONES_PER_OCTAVE - self.root as u32;
+
+        for distance in 0..=SEMITONES_PER_OCTAVE / 2 {
+            if distance <= semitones && self.scale.contains(degree - distance) {
+                return semitones - distance
+            }
+            if self.scale.contains(degree + distance) {
+                return semitones + distance
+            }
+        }
+
+        semitones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_passes_notes_through_chromatic() {
+        let quantizer: Quantizer = Default::default();
+
+        for semitones in 0..48 {
+            assert_eq!(semitones, quantizer.quantize_semitones(semitones));
+        }
+    }
+
+    #[test]
+    fn it_snaps_to_the_nearest_note_of_the_scale() {
+        let quantizer = Quantizer::new(Scale::Major, 0);
+
+        assert_eq!(Note::new(3, 0), quantizer.quantize(Note::new(3, 1)));
+        assert_eq!(Note::new(3, 5), quantizer.quantize(Note::new(3, 6)));
+        assert_eq!(Note::new(3, 11), quantizer.quantize(Note::new(3, 11)));
+    }
+
+    #[test]
+    fn it_snaps_relative_to_the_root() {
+        // D major has F# and C#.
+        let quantizer = Quantizer::new(Scale::Major, 2);
+
+        assert_eq!(Note::new(3, 6), quantizer.quantize(Note::new(3, 6)));
+        assert_eq!(Note::new(3, 1), quantizer.quantize(Note::new(3, 1)));
+        assert_eq!(Note::new(3, 4), quantizer.quantize(Note::new(3, 5)));
+    }
+
+    #[test]
+    fn it_snaps_across_the_octave() {
+        let quantizer = Quantizer::new(Scale::Custom(0b1), 0);
+
+        assert_eq!(Note::new(3, 0), quantizer.quantize(Note::new(2, 7)));
+        assert_eq!(Note::new(2, 0), quantizer.quantize(Note::new(2, 6)));
+        assert_eq!(Note::new(0, 0), quantizer.quantize(Note::new(0, 3)));
+    }
+
+    #[test]
+    fn it_quantizes_a_voltage() {
+        let quantizer = Quantizer::new(Scale::MinorPentatonic, 9);
+
+        // A minor pentatonic: A C D E G.
+        assert_eq!(Note::new(2, 9), quantizer.quantize_millivolts(2_750));
+        assert_eq!(Note::new(2, 7), quantizer.quantize_millivolts(2_620));
+    }
+
+    #[test]
+    fn it_leaves_notes_alone_in_an_empty_scale() {
+        let quantizer = Quantizer::new(Scale::Custom(0), 0);
+
+        assert_eq!(Note::new(1, 1), quantizer.quantize(Note::new(1, 1)));
+    }
+}
@@ -0,0 +1,20 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ratchet {
+    #[default]
+    R1,
+    R2,
+    R3,
+    R4,
+}
+
+impl From<Ratchet> for u32 {
+    fn from(val: Ratchet) -> Self {
+        match val {
+            Ratchet::R1 => 1,
+            Ratchet::R2 => 2,
+            Ratchet::R3 => 3,
+            Ratchet::R4 => 4,
+        }
+    }
+}
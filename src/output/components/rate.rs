@@ -1,10 +1,35 @@
 use super::Frac;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Rate {
     Div(u32, Frac),
     Unity,
     Mult(u32, Frac),
+    /// `num` steps in the time of `den` beats, e.g. `Ratio(3, 2)` plays
+    /// three against two. Both are treated as at least 1 and there is at
+    /// most one step per tick, so a ratchet may be cut short.
+    Ratio(u32, u32),
+}
+
+impl Rate {
+    /// Length of a step at `resolution` ticks per beat as `ticks` per
+    /// `steps`.
+    ///
+    /// Ratios are kept exact: step `n` starts `n * ticks / steps` ticks in,
+    /// rounded down, so steps that don't divide the beat grid evenly
+    /// differ by at most a tick and every `steps` steps land exactly on
+    /// `ticks`, however long the sequence runs. Other rates have a whole
+    /// number of ticks per step.
+    pub fn step_ticks(&self, resolution: u32) -> (u64, u32) {
+        match *self {
+            Rate::Ratio(num, den) => {
+                let ticks = resolution as u64 * den.max(1) as u64;
+                (ticks, (num.max(1) as u64).min(ticks) as u32)
+            }
+            rate => ((Into::<f32>::into(rate) * resolution as f32) as u64, 1),
+        }
+    }
 }
 
 impl From<Rate> for f32 {
@@ -13,6 +38,30 @@ impl From<Rate> for f32 {
             Rate::Div(div, frac) => div as f32 + Into::<f32>::into(frac),
             Rate::Unity => 1.0,
             Rate::Mult(mult, frac) => (1.0 / mult as f32) + Into::<f32>::into(frac),
+            Rate::Ratio(num, den) => den.max(1) as f32 / num.max(1) as f32,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::RESOLUTION;
+
+    #[test]
+    fn it_keeps_ratios_exact() {
+        assert_eq!((5_760, 7), Rate::Ratio(7, 3).step_ticks(RESOLUTION));
+        assert_eq!((1_920, 1), Rate::Ratio(0, 0).step_ticks(RESOLUTION));
+        assert_eq!((1_920, 1_920), Rate::Ratio(5_000, 1).step_ticks(RESOLUTION));
+    }
+
+    #[test]
+    fn it_has_whole_ticks_per_step_for_other_rates() {
+        assert_eq!((1_920, 1), Rate::Unity.step_ticks(RESOLUTION));
+        assert_eq!((640, 1), Rate::Mult(3, Frac::Zero).step_ticks(RESOLUTION));
+        assert_eq!(
+            (4_800, 1),
+            Rate::Div(2, Frac::OneHalf).step_ticks(RESOLUTION)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+/// When a rate change set with `Seq::set_rate` takes effect.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RateAlign {
+    /// Right away on the grid of the new rate, which may cut the current
+    /// step short or land mid-step.
+    #[default]
+    Immediate,
+    /// When the current step ends.
+    Step,
+    /// On the next beat.
+    Beat,
+    /// On the next bar of `BEATS_PER_BAR` beats.
+    Bar,
+}
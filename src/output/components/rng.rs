@@ -2,11 +2,13 @@ use oorandom::Rand32;
 
 use super::Prob;
 
-const RNG_SEED: u64 = 0;
+pub const RNG_SEED: u64 = 0;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Rng {
     rng: Rand32,
+    locked: bool,
+    loop_state: Option<(u64, u64)>,
 }
 
 impl Default for Rng {
@@ -17,14 +19,51 @@ impl Default for Rng {
 
 impl Rng {
     pub fn new() -> Self {
+        Self::from_rand(Rand32::new(RNG_SEED))
+    }
+
+    /// Seeds one of many independent streams, so outputs sharing a seed
+    /// still make different decisions.
+    pub fn with_seed(seed: u64, stream: u64) -> Self {
+        Self::from_rand(Rand32::new_inc(seed, stream))
+    }
+
+    fn from_rand(rng: Rand32) -> Self {
         Self {
-            rng: Rand32::new(RNG_SEED),
+            rng,
+            locked: false,
+            loop_state: Option::None,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// While locked the decisions made during one loop of the pattern are
+    /// replayed on every following loop, Turing Machine style.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    /// Called when the pattern starts over. Records the state of the
+    /// generator on the first loop after locking and rewinds to it on
+    /// every loop after that.
+    pub fn restart_loop(&mut self) {
+        match (self.locked, self.loop_state) {
+            (true, Option::Some(state)) => self.rng = Rand32::from_state(state),
+            (true, Option::None) => self.loop_state = Option::Some(self.rng.state()),
+            (false, _) => self.loop_state = Option::None,
         }
     }
 
     pub fn rand_bool(&mut self, prob: Prob) -> bool {
         prob.rand_bool(&mut self.rng)
     }
+
+    pub fn rand_u16(&mut self) -> u16 {
+        (self.rng.rand_u32() >> 16) as u16
+    }
 }
 
 #[cfg(test)]
@@ -51,4 +90,45 @@ mod tests {
 
         assert!(!rng.rand_bool(Prob::P10));
     }
+
+    #[test]
+    fn it_makes_different_decisions_for_different_streams() {
+        let mut first = Rng::with_seed(RNG_SEED, 0);
+        let mut second = Rng::with_seed(RNG_SEED, 1);
+
+        let first: [bool; 16] = core::array::from_fn(|_| first.rand_bool(Prob::P50));
+        let second: [bool; 16] = core::array::from_fn(|_| second.rand_bool(Prob::P50));
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn it_makes_different_decisions_for_different_seeds() {
+        let mut first = Rng::with_seed(1, 0);
+        let mut second = Rng::with_seed(2, 0);
+
+        let first: [bool; 16] = core::array::from_fn(|_| first.rand_bool(Prob::P50));
+        let second: [bool; 16] = core::array::from_fn(|_| second.rand_bool(Prob::P50));
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn it_replays_decisions_while_locked() {
+        let mut rng = Rng::new();
+        rng.set_locked(true);
+
+        rng.restart_loop();
+        let first: [bool; 8] = core::array::from_fn(|_| rng.rand_bool(Prob::P50));
+        rng.restart_loop();
+        let second: [bool; 8] = core::array::from_fn(|_| rng.rand_bool(Prob::P50));
+
+        assert_eq!(first, second);
+
+        rng.set_locked(false);
+        rng.restart_loop();
+        let third: [bool; 8] = core::array::from_fn(|_| rng.rand_bool(Prob::P50));
+
+        assert_ne!(first, third);
+    }
 }
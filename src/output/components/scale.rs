@@ -0,0 +1,71 @@
+use super::SEMITONES_PER_OCTAVE;
+
+/// Notes allowed by a scale, as a 12-bit mask where bit `n` is the note `n`
+/// semitones above the root.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scale {
+    #[default]
+    Chromatic,
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    /// Only the lower 12 bits are used.
+    Custom(u16),
+}
+
+impl Scale {
+    pub fn mask(&self) -> u16 {
+        match self {
+            Scale::Chromatic => 0b1111_1111_1111,
+            Scale::Major => 0b1010_1011_0101,
+            Scale::Minor => 0b0101_1010_1101,
+            Scale::Dorian => 0b0110_1010_1101,
+            Scale::Phrygian => 0b0101_1010_1011,
+            Scale::Lydian => 0b1010_1101_0101,
+            Scale::Mixolydian => 0b0110_1011_0101,
+            Scale::Locrian => 0b0101_0110_1011,
+            Scale::MajorPentatonic => 0b0010_1001_0101,
+            Scale::MinorPentatonic => 0b0100_1010_1001,
+            Scale::Custom(mask) => mask & 0b1111_1111_1111,
+        }
+    }
+
+    pub fn contains(&self, degree: u32) -> bool {
+        self.mask() & (1 << (degree % SEMITONES_PER_OCTAVE)) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn degrees(scale: Scale) -> Vec<u32> {
+        (0..12).filter(|&degree| scale.contains(degree)).collect()
+    }
+
+    #[test]
+    fn it_masks_the_degrees_of_each_scale() {
+        assert_eq!(vec![0, 2, 4, 5, 7, 9, 11], degrees(Scale::Major));
+        assert_eq!(vec![0, 2, 3, 5, 7, 8, 10], degrees(Scale::Minor));
+        assert_eq!(vec![0, 2, 3, 5, 7, 9, 10], degrees(Scale::Dorian));
+        assert_eq!(vec![0, 1, 3, 5, 7, 8, 10], degrees(Scale::Phrygian));
+        assert_eq!(vec![0, 2, 4, 6, 7, 9, 11], degrees(Scale::Lydian));
+        assert_eq!(vec![0, 2, 4, 5, 7, 9, 10], degrees(Scale::Mixolydian));
+        assert_eq!(vec![0, 1, 3, 5, 6, 8, 10], degrees(Scale::Locrian));
+        assert_eq!(vec![0, 2, 4, 7, 9], degrees(Scale::MajorPentatonic));
+        assert_eq!(vec![0, 3, 5, 7, 10], degrees(Scale::MinorPentatonic));
+        assert_eq!(12, degrees(Scale::Chromatic).len());
+    }
+
+    #[test]
+    fn it_ignores_bits_above_the_octave_of_a_custom_scale() {
+        assert_eq!(0b1001, Scale::Custom(0xF009).mask());
+    }
+}
@@ -0,0 +1,11 @@
+use super::{Note, Ratchet};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    pub on: bool,
+    pub ratchet: Ratchet,
+    pub note: Note,
+    /// Holds the gate into the next step so it plays legato.
+    pub tie: bool,
+}
@@ -0,0 +1,65 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Swing {
+    P50,
+    P54,
+    P58,
+    P62,
+    P66,
+    P71,
+    P75,
+}
+
+impl From<Swing> for u32 {
+    fn from(val: Swing) -> Self {
+        match val {
+            Swing::P50 => 50,
+            Swing::P54 => 54,
+            Swing::P58 => 58,
+            Swing::P62 => 62,
+            Swing::P66 => 66,
+            Swing::P71 => 71,
+            Swing::P75 => 75,
+        }
+    }
+}
+
+impl Swing {
+    /// Ticks every even-numbered step is delayed by, for steps
+    /// `cycle_target` ticks long. The percentage is where the delayed step
+    /// lands within the pair of steps it belongs to, so P50 is straight.
+    pub fn delay(&self, cycle_target: u32) -> u32 {
+        let percent: u32 = (*self).into();
+        (cycle_target as u64 * (2 * percent - 100) as u64 / 100) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::RESOLUTION;
+
+    #[test]
+    fn it_calcs_no_delay_for_p50() {
+        let result = Swing::P50.delay(RESOLUTION);
+        let expected = 0;
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_calcs_delay_for_p66() {
+        let result = Swing::P66.delay(RESOLUTION);
+        let expected = 614;
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn it_calcs_delay_for_p75() {
+        let result = Swing::P75.delay(RESOLUTION);
+        let expected = 960;
+
+        assert_eq!(expected, result);
+    }
+}
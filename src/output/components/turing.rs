@@ -0,0 +1,50 @@
+use super::Length;
+
+/// Longest shift register, in bits.
+pub const MAX_REGISTER_BITS: u32 = u32::BITS;
+
+/// Advances a Turing Machine style shift register of `l` bits by one step.
+///
+/// The oldest bit is fed back in as the newest, flipped when `flip` is
+/// set, so the register loops every `l` steps unless it is flipped.
+/// Registers longer than `MAX_REGISTER_BITS` are clamped.
+pub fn turing(register: u32, l: Length, flip: bool) -> u32 {
+    let bits = l.0.clamp(1, MAX_REGISTER_BITS);
+    let mask = u32::MAX >> (MAX_REGISTER_BITS - bits);
+    let oldest = (register >> (bits - 1)) & 1;
+
+    ((register << 1) | (oldest ^ flip as u32)) & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_loops_without_flips() {
+        let mut register = 0b1011;
+
+        for _ in 0..4 {
+            register = turing(register, Length(4), false);
+        }
+
+        assert_eq!(0b1011, register);
+    }
+
+    #[test]
+    fn it_rotates_the_oldest_bit_in() {
+        assert_eq!(0b0111, turing(0b1011, Length(4), false));
+        assert_eq!(0b0110, turing(0b1011, Length(4), true));
+    }
+
+    #[test]
+    fn it_fills_an_empty_register_with_flips() {
+        assert_eq!(0b1, turing(0, Length(8), true));
+        assert_eq!(0b11, turing(0b1, Length(8), true));
+    }
+
+    #[test]
+    fn it_clamps_long_registers() {
+        assert_eq!(1, turing(1 << 31, Length(64), false));
+    }
+}
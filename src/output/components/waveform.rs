@@ -0,0 +1,137 @@
+/// One full cycle of an LFO phase.
+pub const PHASE_CYCLE: u32 = 1 << 16;
+const PHASE_HALF: u32 = PHASE_CYCLE / 2;
+const PHASE_QUARTER: u32 = PHASE_CYCLE / 4;
+const VALUE_MAX: u32 = u16::MAX as u32;
+
+const SINE_MAX: i32 = i16::MAX as i32;
+
+/// A quarter of a sine wave scaled to `SINE_MAX`.
+const QUARTER_SINE: [i32; 65] = [
+    0, 804, 1_608, 2_410, 3_212, 4_011, 4_808, 5_602, 6_393, 7_179, 7_962, 8_739, 9_512, 10_278,
+    11_039, 11_793, 12_539, 13_279, 14_010, 14_732, 15_446, 16_151, 16_846, 17_530, 18_204, 18_868,
+    19_519, 20_159, 20_787, 21_403, 22_005, 22_594, 23_170, 23_731, 24_279, 24_811, 25_329, 25_832,
+    26_319, 26_790, 27_245, 27_683, 28_105, 28_510, 28_898, 29_268, 29_621, 29_956, 30_273, 30_571,
+    30_852, 31_113, 31_356, 31_580, 31_785, 31_971, 32_137, 32_285, 32_412, 32_521, 32_609, 32_678,
+    32_728, 32_757, 32_767,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    SampleAndHold,
+}
+
+impl Waveform {
+    /// Value at `phase` of a cycle `PHASE_CYCLE` long. Sine and triangle
+    /// start at their lowest and peak half way through, square is high for
+    /// the first half like a gate and sample & hold outputs `sample`
+    /// throughout.
+    pub fn value(&self, phase: u32, sample: u16) -> u16 {
+        let phase = phase % PHASE_CYCLE;
+
+        let value = match self {
+            Waveform::Sine => {
+                let cosine = sine(phase + PHASE_QUARTER);
+                (SINE_MAX - cosine) as u32 * VALUE_MAX / (2 * SINE_MAX as u32)
+            }
+            Waveform::Triangle if phase < PHASE_HALF => phase * 2,
+            Waveform::Triangle => (PHASE_CYCLE - phase) * 2,
+            Waveform::Saw => phase,
+            Waveform::Square if phase < PHASE_HALF => VALUE_MAX,
+            Waveform::Square => 0,
+            Waveform::SampleAndHold => sample as u32,
+        };
+
+        value.min(VALUE_MAX) as u16
+    }
+}
+
+/// Sine of `phase` scaled to `SINE_MAX`, interpolated from a quarter wave
+/// table.
+fn sine(phase: u32) -> i32 {
+    let phase = phase % PHASE_CYCLE;
+    let offset = phase % PHASE_QUARTER;
+
+    match phase / PHASE_QUARTER {
+        0 => quarter_sine(offset),
+        1 => quarter_sine(PHASE_QUARTER - offset),
+        2 => -quarter_sine(offset),
+        _ => -quarter_sine(PHASE_QUARTER - offset),
+    }
+}
+
+fn quarter_sine(offset: u32) -> i32 {
+    const STEP: u32 = PHASE_QUARTER / 64;
+
+    let index = (offset / STEP) as usize;
+    let frac = (offset % STEP) as i32;
+    if index == QUARTER_SINE.len() - 1 {
+        return QUARTER_SINE[index]
+    }
+
+    let (from, to) = (QUARTER_SINE[index], QUARTER_SINE[index + 1]);
+    from + (to - from) * frac / STEP as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_starts_low_and_peaks_half_way() {
+        for waveform in [Waveform::Sine, Waveform::Triangle] {
+            let start = waveform.value(0, 0);
+            let peak = waveform.value(PHASE_HALF - 1, 0);
+
+            assert!(start < peak, "{:?}", waveform);
+        }
+
+        assert_eq!(0, Waveform::Sine.value(0, 0));
+        assert_eq!(u16::MAX, Waveform::Sine.value(PHASE_HALF, 0));
+        assert_eq!(u16::MAX, Waveform::Triangle.value(PHASE_HALF, 0));
+        assert_eq!(0, Waveform::Triangle.value(0, 0));
+    }
+
+    #[test]
+    fn it_follows_a_sine() {
+        for phase in (0..PHASE_CYCLE).step_by(97) {
+            let expected = (1.0
+                - (phase as f64 / PHASE_CYCLE as f64 * 2.0 * core::f64::consts::PI).cos())
+                * 32_767.5;
+            let value = Waveform::Sine.value(phase, 0) as f64;
+
+            assert!(
+                (value - expected).abs() < 32.0,
+                "{} {} {}",
+                phase,
+                value,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn it_is_high_for_the_first_half_of_a_square() {
+        assert_eq!(u16::MAX, Waveform::Square.value(0, 0));
+        assert_eq!(0, Waveform::Square.value(PHASE_HALF, 0));
+    }
+
+    #[test]
+    fn it_ramps_a_saw() {
+        assert_eq!(0, Waveform::Saw.value(0, 0));
+        assert_eq!(32_768, Waveform::Saw.value(PHASE_HALF, 0));
+        assert_eq!(u16::MAX, Waveform::Saw.value(PHASE_CYCLE - 1, 0));
+    }
+
+    #[test]
+    fn it_holds_the_sample() {
+        assert_eq!(1_234, Waveform::SampleAndHold.value(0, 1_234));
+        assert_eq!(1_234, Waveform::SampleAndHold.value(PHASE_HALF, 1_234));
+    }
+}
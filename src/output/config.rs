@@ -3,48 +3,80 @@ use heapless::Vec;
 use self::components::MAX_STEPS;
 use super::*;
 
+/// A config of the default size, see `ConfigN`.
+pub type Config = ConfigN<MAX_STEPS>;
+
 #[derive(Clone, Debug, PartialEq)]
-pub struct Config {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigN<const STEPS: usize> {
+    accent: AccentN<STEPS>,
     density: Density,
+    envelope: Envelope,
     length: Length,
+    logic: Logic,
     output_type: OutputType,
     prob: Prob,
     pwm: Pwm,
+    quantizer: Quantizer,
     rate: Rate,
-    sequence: Sequence,
+    rotation: Rotation,
+    sequence: SequenceN<STEPS>,
+    swing: Swing,
+    waveform: Waveform,
 }
 
-impl Default for Config {
+impl<const STEPS: usize> Default for ConfigN<STEPS> {
     fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Config {
-    pub fn new() -> Self {
         let mut config = Self {
-            density: Density(4),
-            length: Length(MAX_STEPS as u32),
+            accent: Default::default(),
+            density: Density(4.min(STEPS as u32)),
+            envelope: Default::default(),
+            length: Length(STEPS as u32),
+            logic: Default::default(),
             output_type: OutputType::Gate,
             prob: Prob::P100,
             pwm: Pwm::P50,
+            quantizer: Default::default(),
             rate: Rate::Unity,
+            rotation: Rotation(0),
             sequence: Vec::new(),
+            swing: Swing::P50,
+            waveform: Default::default(),
         };
 
         config.set_output_type(config.output_type);
 
         config
     }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<const STEPS: usize> ConfigN<STEPS> {
+    pub fn accent(&self) -> &AccentN<STEPS> {
+        &self.accent
+    }
 
     pub fn density(&self) -> Density {
         self.density
     }
 
+    pub fn envelope(&self) -> Envelope {
+        self.envelope
+    }
+
     pub fn length(&self) -> Length {
         self.length
     }
 
+    pub fn logic(&self) -> Logic {
+        self.logic
+    }
+
     pub fn output_type(&self) -> OutputType {
         self.output_type
     }
@@ -57,32 +89,64 @@ impl Config {
         self.pwm
     }
 
+    pub fn quantizer(&self) -> Quantizer {
+        self.quantizer
+    }
+
     pub fn rate(&self) -> Rate {
         self.rate
     }
 
-    pub fn sequence(&self) -> &Sequence {
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    pub fn sequence(&self) -> &SequenceN<STEPS> {
         &self.sequence
     }
 
+    pub fn swing(&self) -> Swing {
+        self.swing
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
     pub fn set_sequence(&mut self, length: Length, density: Density) {
         self.length = length;
         self.density = density;
-        euclid(self.density, self.length, &mut self.sequence);
+        self.build_sequence();
     }
 
     pub fn set_output_type(&mut self, output_type: OutputType) {
         self.output_type = output_type;
-        let density = match output_type {
-            OutputType::Gate => Density(self.length.0),
-            OutputType::Euclid => self.density,
-        };
         let prob = match output_type {
-            OutputType::Gate => self.prob,
             OutputType::Euclid => Prob::P100,
+            _ => self.prob,
         };
         self.set_prob(prob);
-        euclid(density, self.length, &mut self.sequence);
+        self.build_sequence();
+    }
+
+    fn build_sequence(&mut self) {
+        let density = match self.output_type {
+            OutputType::Gate
+            | OutputType::Pitch
+            | OutputType::Lfo
+            | OutputType::Turing
+            | OutputType::Logic => Density(self.length.0),
+            OutputType::Euclid | OutputType::Envelope => self.density,
+        };
+        euclid_n(density, self.length, self.rotation, &mut self.sequence);
+    }
+
+    pub fn set_envelope(&mut self, envelope: Envelope) {
+        self.envelope = envelope;
+    }
+
+    pub fn set_logic(&mut self, logic: Logic) {
+        self.logic = logic;
     }
 
     pub fn set_prob(&mut self, prob: Prob) {
@@ -93,7 +157,46 @@ impl Config {
         self.pwm = pwm;
     }
 
+    pub fn set_quantizer(&mut self, quantizer: Quantizer) {
+        self.quantizer = quantizer;
+    }
+
     pub fn set_rate(&mut self, rate: Rate) {
         self.rate = rate;
     }
+
+    pub fn set_accent(&mut self, length: Length, density: Density, rotation: Rotation) {
+        self.accent.set(length, density, rotation);
+    }
+
+    pub fn set_ratchet(&mut self, step: usize, ratchet: Ratchet) {
+        if let Option::Some(step) = self.sequence.get_mut(step) {
+            step.ratchet = ratchet;
+        }
+    }
+
+    pub fn set_note(&mut self, step: usize, note: Note) {
+        if let Option::Some(step) = self.sequence.get_mut(step) {
+            step.note = note;
+        }
+    }
+
+    pub fn set_tie(&mut self, step: usize, tie: bool) {
+        if let Option::Some(step) = self.sequence.get_mut(step) {
+            step.tie = tie;
+        }
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+        self.build_sequence();
+    }
+
+    pub fn set_swing(&mut self, swing: Swing) {
+        self.swing = swing;
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
 }
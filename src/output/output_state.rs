@@ -1,14 +1,26 @@
 use heapless::Vec;
 
-use super::Rng;
+use super::{EnvelopeState, Note, Rng};
 
-pub type OutputStates = Vec<OutputState, 4>;
+pub const MAX_OUTPUTS: usize = 4;
+
+pub type OutputStatesN<const OUTPUTS: usize> = Vec<OutputState, OUTPUTS>;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct OutputState {
+    pub accent: bool,
+    pub envelope: EnvelopeState,
     pub index: usize,
     pub index_change: bool,
+    /// `register` as the sequence last started over.
+    pub loop_register: u32,
+    /// Note of the last step played.
+    pub note: Note,
     pub on: bool,
     pub on_change: bool,
+    /// Shift register of a Turing Machine output.
+    pub register: u32,
     pub rng: Rng,
+    /// Sample & hold value of the current LFO cycle.
+    pub sample: u16,
 }
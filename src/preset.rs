@@ -0,0 +1,449 @@
+use heapless::Vec;
+
+pub use self::bank::{PresetBank, Recall, BEATS_PER_BAR};
+use self::codec::{Decode, Encode, Reader, Writer, CONFIG_LEN, RECORD_LEN, STEP_LEN};
+use crate::{
+    bpm::{Bpm, BPM_SCALE},
+    output::{ConfigN, MAX_OUTPUTS, MAX_STEPS},
+};
+
+mod bank;
+mod codec;
+
+/// Bumped whenever fields are appended. Fields are only ever appended to
+/// length prefixed records, so presets written by newer firmware decode on
+/// older firmware with the fields it knows, and older presets decode on
+/// newer firmware.
+pub const PRESET_VERSION: u8 = 7;
+
+const MAGIC: [u8; 2] = *b"SQ";
+const CONFIG_KIND: u8 = 0;
+const SEQ_KIND: u8 = 1;
+const HEADER_LEN: usize = 6;
+const CHECKSUM_LEN: usize = 2;
+const BPM_LEN: usize = 4;
+const CONFIG_COUNT_LEN: usize = 1;
+const BPM_FRACTION_LEN: usize = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PresetError {
+    BufferTooSmall,
+    Truncated,
+    BadHeader,
+    Checksum,
+    Invalid,
+}
+
+/// Compact binary encoding for storing to flash.
+///
+/// The layout is a header of magic, version, kind and payload length,
+/// followed by the payload and a CRC-16 of everything before it.
+pub trait Preset: Sized {
+    /// Encodes into `buf`, returning the number of bytes written.
+    fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, PresetError>;
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, PresetError>;
+}
+
+/// A preset of the default size, see `SeqPresetN`.
+pub type SeqPreset = SeqPresetN<MAX_OUTPUTS, MAX_STEPS>;
+
+/// The tempo and output configs of a `Seq`. Random seeds and locks, the
+/// clock source and the rate alignment are left as they are on load.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeqPresetN<const OUTPUTS: usize, const STEPS: usize> {
+    pub bpm: Bpm,
+    pub configs: Vec<ConfigN<STEPS>, OUTPUTS>,
+}
+
+// Whole BPM, then each config. Version 7 appends the hundredths of the
+// BPM.
+impl<const OUTPUTS: usize, const STEPS: usize> Encode for SeqPresetN<OUTPUTS, STEPS> {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.record(|writer| {
+            writer.u32(self.bpm.hundredths() / BPM_SCALE)?;
+            writer.u8(self.configs.len() as u8)?;
+            self.configs
+                .iter()
+                .try_for_each(|config| config.encode(writer))?;
+            writer.u8((self.bpm.hundredths() % BPM_SCALE) as u8)
+        })
+    }
+}
+
+impl<const OUTPUTS: usize, const STEPS: usize> Decode for SeqPresetN<OUTPUTS, STEPS> {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let mut reader = reader.record()?;
+        let whole = reader.u32()?;
+        let len = reader.u8()? as usize;
+
+        if len > OUTPUTS {
+            return Err(PresetError::Invalid)
+        }
+
+        let mut configs = Vec::new();
+        for _ in 0..len {
+            configs.push(ConfigN::decode(&mut reader)?).ok();
+        }
+
+        let fraction = if reader.is_empty() {
+            0
+        } else {
+            reader.u8()? as u32
+        };
+        let hundredths = whole
+            .checked_mul(BPM_SCALE)
+            .and_then(|hundredths| hundredths.checked_add(fraction))
+            .filter(|&hundredths| hundredths > 0 && fraction < BPM_SCALE)
+            .ok_or(PresetError::Invalid)?;
+
+        Ok(Self {
+            bpm: Bpm::from_hundredths(hundredths),
+            configs,
+        })
+    }
+}
+
+impl<const STEPS: usize> Preset for ConfigN<STEPS> {
+    fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, PresetError> {
+        write(CONFIG_KIND, self, buf)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, PresetError> {
+        read(CONFIG_KIND, buf)
+    }
+}
+
+impl<const OUTPUTS: usize, const STEPS: usize> Preset for SeqPresetN<OUTPUTS, STEPS> {
+    fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, PresetError> {
+        write(SEQ_KIND, self, buf)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, PresetError> {
+        read(SEQ_KIND, buf)
+    }
+}
+
+fn write(kind: u8, value: &impl Encode, buf: &mut [u8]) -> Result<usize, PresetError> {
+    let mut writer = Writer::new(buf);
+    writer.u8(MAGIC[0])?;
+    writer.u8(MAGIC[1])?;
+    writer.u8(PRESET_VERSION)?;
+    writer.u8(kind)?;
+    writer.u16(0)?;
+    value.encode(&mut writer)?;
+
+    let payload_len = writer.len() - HEADER_LEN;
+    writer.set_u16(4, payload_len as u16);
+    let checksum = crc16(writer.written());
+    writer.u16(checksum)?;
+
+    Ok(writer.len())
+}
+
+fn read<T: Decode>(kind: u8, buf: &[u8]) -> Result<T, PresetError> {
+    let mut reader = Reader::new(buf);
+    let header = reader.bytes(HEADER_LEN)?;
+
+    if header[..2] != MAGIC || header[2] == 0 || header[3] != kind {
+        return Err(PresetError::BadHeader)
+    }
+
+    let payload_len = u16::from_le_bytes([header[4], header[5]]) as usize;
+    let payload = reader.bytes(payload_len)?;
+    let checksum = reader.u16()?;
+
+    if checksum != crc16(&buf[..HEADER_LEN + payload_len]) {
+        return Err(PresetError::Checksum)
+    }
+
+    T::decode(&mut Reader::new(payload))
+}
+
+// CRC-16/CCITT-FALSE
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xffff, |crc, &byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Bytes needed to store any `SeqPreset` of `outputs` outputs of `steps`
+/// steps.
+pub const fn preset_len(outputs: usize, steps: usize) -> usize {
+    HEADER_LEN
+        + RECORD_LEN
+        + BPM_LEN
+        + CONFIG_COUNT_LEN
+        + outputs * (CONFIG_LEN + steps * STEP_LEN)
+        + BPM_FRACTION_LEN
+        + CHECKSUM_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{
+        Config, Density, Envelope, EnvelopeTime, Frac, Length, Logic, LogicOp, Note, OutputType,
+        Prob, Pwm, Quantizer, Ratchet, Rate, Rotation, Scale, Swing, Waveform,
+    };
+
+    fn config() -> Config {
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Euclid);
+        config.set_sequence(Length(13), Density(5));
+        config.set_rotation(Rotation(3));
+        config.set_prob(Prob::P70);
+        config.set_pwm(Pwm::Pew);
+        config.set_rate(Rate::Mult(3, Frac::OneHalf));
+        config.set_swing(Swing::P62);
+        config.set_accent(Length(5), Density(2), Rotation(1));
+        config.set_ratchet(4, Ratchet::R3);
+        config
+    }
+
+    fn pitch_config() -> Config {
+        let mut config = Config::new();
+        config.set_output_type(OutputType::Pitch);
+        config.set_sequence(Length(4), Density(4));
+        config.set_note(1, Note::new(3, 7));
+        config.set_note(2, Note::new(4, 0));
+        config.set_tie(2, true);
+        config.set_quantizer(Quantizer::new(Scale::Custom(0b1001_0001), 4));
+        config.set_waveform(Waveform::SampleAndHold);
+        config.set_envelope(Envelope::Adsr {
+            attack: EnvelopeTime::Ticks(10),
+            decay: EnvelopeTime::StepPercent(25),
+            sustain: 40_000,
+            release: EnvelopeTime::StepPercent(150),
+        });
+        config.set_logic(Logic::new(LogicOp::Xor, 0, 2));
+        config
+    }
+
+    #[test]
+    fn it_computes_crc16() {
+        assert_eq!(0x29b1, crc16(b"123456789"));
+    }
+
+    #[test]
+    fn it_round_trips_a_config() {
+        let mut buf = [0; 128];
+        let config = config();
+
+        let len = config.to_bytes(&mut buf).unwrap();
+
+        assert_eq!(Ok(config), Config::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_round_trips_notes_ties_and_quantizer() {
+        let mut buf = [0; 128];
+        let config = pitch_config();
+
+        let len = config.to_bytes(&mut buf).unwrap();
+
+        assert_eq!(Ok(config), Config::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_round_trips_a_seq_preset() {
+        let mut buf = [0; preset_len(MAX_OUTPUTS, MAX_STEPS)];
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(4).ok();
+        configs[2] = config();
+        let preset = SeqPreset {
+            bpm: Bpm::from_hundredths(9_875),
+            configs,
+        };
+
+        let len = preset.to_bytes(&mut buf).unwrap();
+
+        assert_eq!(Ok(preset), SeqPreset::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_round_trips_the_default_config_of_a_short_sequence() {
+        let mut buf = [0; 128];
+        let config: ConfigN<2> = Default::default();
+
+        let len = config.to_bytes(&mut buf).unwrap();
+
+        assert_eq!(Ok(config), ConfigN::<2>::from_bytes(&buf[..len]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_round_trips_a_seq_preset_through_serde() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.push(config()).ok();
+        configs.push(pitch_config()).ok();
+        let preset = SeqPreset {
+            bpm: Bpm::from_hundredths(9_875),
+            configs,
+        };
+
+        let json = serde_json::to_string(&preset).unwrap();
+
+        assert_eq!(preset, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn it_fits_the_largest_seq_preset_in_preset_len() {
+        let mut buf = [0; preset_len(2, 8)];
+        let mut config: ConfigN<8> = Default::default();
+        config.set_rate(Rate::Ratio(7, 8));
+        let mut configs: Vec<ConfigN<8>, 2> = Vec::new();
+        configs.resize(2, config).ok();
+        let preset = SeqPresetN {
+            bpm: Bpm::from_hundredths(12_099),
+            configs,
+        };
+
+        assert_eq!(Ok(buf.len()), preset.to_bytes(&mut buf));
+    }
+
+    #[test]
+    fn it_rejects_a_corrupted_preset() {
+        let mut buf = [0; 128];
+        let len = config().to_bytes(&mut buf).unwrap();
+        buf[10] ^= 0x01;
+
+        assert_eq!(Err(PresetError::Checksum), Config::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_rejects_a_preset_of_another_kind() {
+        let mut buf = [0; 128];
+        let len = config().to_bytes(&mut buf).unwrap();
+
+        assert_eq!(
+            Err(PresetError::BadHeader),
+            SeqPreset::from_bytes(&buf[..len])
+        );
+    }
+
+    #[test]
+    fn it_rejects_a_truncated_preset() {
+        let mut buf = [0; 128];
+        let len = config().to_bytes(&mut buf).unwrap();
+
+        assert_eq!(
+            Err(PresetError::Truncated),
+            Config::from_bytes(&buf[..len - 1])
+        );
+    }
+
+    #[test]
+    fn it_fails_to_encode_into_a_small_buffer() {
+        let mut buf = [0; 16];
+
+        assert_eq!(
+            Err(PresetError::BufferTooSmall),
+            config().to_bytes(&mut buf)
+        );
+    }
+
+    #[test]
+    fn it_decodes_a_preset_from_a_newer_version_with_appended_fields() {
+        let mut buf = [0; 128];
+        let config = config();
+        let mut writer = Writer::new(&mut buf);
+        writer.u8(MAGIC[0]).unwrap();
+        writer.u8(MAGIC[1]).unwrap();
+        writer.u8(PRESET_VERSION + 1).unwrap();
+        writer.u8(CONFIG_KIND).unwrap();
+        writer.u16(0).unwrap();
+        writer
+            .record(|writer| {
+                let mut inner = [0; 128];
+                let mut inner_writer = Writer::new(&mut inner);
+                config.encode(&mut inner_writer)?;
+                inner_writer.written()[2..]
+                    .iter()
+                    .try_for_each(|&byte| writer.u8(byte))?;
+                writer.u32(0xdead_beef)
+            })
+            .unwrap();
+        let payload_len = writer.len() - HEADER_LEN;
+        writer.set_u16(4, payload_len as u16);
+        let checksum = crc16(writer.written());
+        writer.u16(checksum).unwrap();
+        let len = writer.len();
+
+        assert_eq!(Ok(config), Config::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_decodes_a_version_one_preset_without_notes() {
+        let mut buf = [0; 128];
+        let config = config();
+        let mut writer = Writer::new(&mut buf);
+        writer.u8(MAGIC[0]).unwrap();
+        writer.u8(MAGIC[1]).unwrap();
+        writer.u8(1).unwrap();
+        writer.u8(CONFIG_KIND).unwrap();
+        writer.u16(0).unwrap();
+        writer
+            .record(|writer| {
+                let mut inner = [0; 128];
+                let mut inner_writer = Writer::new(&mut inner);
+                config.encode(&mut inner_writer)?;
+                let mut appended = [0; 128];
+                let mut appended_writer = Writer::new(&mut appended);
+                config.sequence().iter().try_for_each(|step| {
+                    step.note.encode(&mut appended_writer)?;
+                    appended_writer.u8(step.tie as u8)
+                })?;
+                config.quantizer().encode(&mut appended_writer)?;
+                config.waveform().encode(&mut appended_writer)?;
+                config.envelope().encode(&mut appended_writer)?;
+                config.logic().encode(&mut appended_writer)?;
+                let notes_len = appended_writer.len();
+                let written = inner_writer.written();
+                written[2..written.len() - notes_len]
+                    .iter()
+                    .try_for_each(|&byte| writer.u8(byte))
+            })
+            .unwrap();
+        let payload_len = writer.len() - HEADER_LEN;
+        writer.set_u16(4, payload_len as u16);
+        let checksum = crc16(writer.written());
+        writer.u16(checksum).unwrap();
+        let len = writer.len();
+
+        assert_eq!(Ok(config), Config::from_bytes(&buf[..len]));
+    }
+
+    #[test]
+    fn it_decodes_a_seq_preset_with_whole_bpm() {
+        let mut buf = [0; 32];
+        let mut writer = Writer::new(&mut buf);
+        writer.u8(MAGIC[0]).unwrap();
+        writer.u8(MAGIC[1]).unwrap();
+        writer.u8(6).unwrap();
+        writer.u8(SEQ_KIND).unwrap();
+        writer.u16(0).unwrap();
+        writer
+            .record(|writer| {
+                writer.u32(98)?;
+                writer.u8(0)
+            })
+            .unwrap();
+        let payload_len = writer.len() - HEADER_LEN;
+        writer.set_u16(4, payload_len as u16);
+        let checksum = crc16(writer.written());
+        writer.u16(checksum).unwrap();
+        let len = writer.len();
+
+        let preset = SeqPreset::from_bytes(&buf[..len]).unwrap();
+
+        assert_eq!(Bpm::new(98), preset.bpm);
+    }
+}
@@ -0,0 +1,323 @@
+use heapless::Vec;
+
+use super::SeqPresetN;
+use crate::{
+    bpm::Bpm,
+    output::{Density, Length, Prob, Pwm, MAX_OUTPUTS, MAX_STEPS},
+    seq::SeqN,
+};
+
+/// Bars are assumed to be in 4/4.
+pub const BEATS_PER_BAR: u32 = 4;
+
+const PROBS: [Prob; 10] = [
+    Prob::P10,
+    Prob::P20,
+    Prob::P30,
+    Prob::P40,
+    Prob::P50,
+    Prob::P60,
+    Prob::P70,
+    Prob::P80,
+    Prob::P90,
+    Prob::P100,
+];
+
+const PWMS: [Pwm; 10] = [
+    Pwm::Pew,
+    Pwm::P10,
+    Pwm::P20,
+    Pwm::P30,
+    Pwm::P40,
+    Pwm::P50,
+    Pwm::P60,
+    Pwm::P70,
+    Pwm::P80,
+    Pwm::P90,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Recall {
+    /// Switches straight away.
+    Immediate,
+    /// Waits for the next bar boundary of the tick count.
+    NextBar,
+}
+
+/// Scenes of full `Seq` snapshots to jump between while playing.
+pub struct PresetBank<
+    const SCENES: usize,
+    const OUTPUTS: usize = MAX_OUTPUTS,
+    const STEPS: usize = MAX_STEPS,
+> {
+    scenes: Vec<Option<SeqPresetN<OUTPUTS, STEPS>>, SCENES>,
+    pending: Option<usize>,
+}
+
+impl<const SCENES: usize, const OUTPUTS: usize, const STEPS: usize> Default
+    for PresetBank<SCENES, OUTPUTS, STEPS>
+{
+    fn default() -> Self {
+        let mut scenes = Vec::new();
+        scenes.resize(SCENES, Option::None).ok();
+
+        Self {
+            scenes,
+            pending: Option::None,
+        }
+    }
+}
+
+impl<const SCENES: usize, const OUTPUTS: usize, const STEPS: usize>
+    PresetBank<SCENES, OUTPUTS, STEPS>
+{
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn scene(&self, index: usize) -> Option<&SeqPresetN<OUTPUTS, STEPS>> {
+        self.scenes.get(index).and_then(Option::as_ref)
+    }
+
+    /// The scene waiting for the next bar, if any.
+    pub fn pending(&self) -> Option<usize> {
+        self.pending
+    }
+
+    pub fn save_scene(&mut self, index: usize, seq: &SeqN<OUTPUTS, STEPS>) {
+        self.scenes[index] = Option::Some(seq.preset());
+    }
+
+    pub fn set_scene(&mut self, index: usize, preset: SeqPresetN<OUTPUTS, STEPS>) {
+        self.scenes[index] = Option::Some(preset);
+    }
+
+    pub fn clear_scene(&mut self, index: usize) {
+        self.scenes[index] = Option::None;
+        if self.pending == Option::Some(index) {
+            self.pending = Option::None;
+        }
+    }
+
+    /// Loads a scene into `seq`, either now or on the next bar boundary.
+    /// A later recall replaces one still pending. Returns `false` if the
+    /// scene is empty.
+    pub fn recall_scene(
+        &mut self,
+        index: usize,
+        seq: &mut SeqN<OUTPUTS, STEPS>,
+        recall: Recall,
+    ) -> bool {
+        let preset = match self.scene(index) {
+            Option::Some(preset) => preset,
+            Option::None => return false,
+        };
+
+        match recall {
+            Recall::Immediate => {
+                seq.load_preset(preset);
+                self.pending = Option::None;
+            }
+            Recall::NextBar => self.pending = Option::Some(index),
+        }
+
+        true
+    }
+
+    /// Loads a blend of two scenes into `seq`, `percent` of the way from
+    /// `from` to `to`. Tempo and the length, density, prob and pwm of each
+    /// output are interpolated, everything else comes from the nearer
+    /// scene. Returns `false` if either scene is empty.
+    pub fn morph_scenes(
+        &self,
+        from: usize,
+        to: usize,
+        percent: u32,
+        seq: &mut SeqN<OUTPUTS, STEPS>,
+    ) -> bool {
+        let (from, to) = match (self.scene(from), self.scene(to)) {
+            (Option::Some(from), Option::Some(to)) => (from, to),
+            _ => return false,
+        };
+        let percent = percent.min(100);
+
+        let mut preset = if percent < 50 {
+            from.clone()
+        } else {
+            to.clone()
+        };
+        preset.bpm =
+            Bpm::from_hundredths(lerp(from.bpm.hundredths(), to.bpm.hundredths(), percent));
+
+        for ((config, from), to) in preset
+            .configs
+            .iter_mut()
+            .zip(from.configs.iter())
+            .zip(to.configs.iter())
+        {
+            let length = lerp(from.length().0, to.length().0, percent);
+            let density = lerp(from.density().0, to.density().0, percent).min(length);
+            config.set_sequence(Length(length), Density(density));
+
+            let prob = lerp(
+                u32::from(from.prob()) - 1,
+                u32::from(to.prob()) - 1,
+                percent,
+            );
+            config.set_prob(PROBS[prob as usize]);
+
+            let pwm = lerp(from.pwm().index() as u32, to.pwm().index() as u32, percent);
+            config.set_pwm(PWMS[pwm as usize]);
+        }
+
+        seq.load_preset(&preset);
+
+        true
+    }
+
+    pub fn cancel_recall(&mut self) {
+        self.pending = Option::None;
+    }
+
+    /// Ticks until the pending scene is loaded, so a scheduler can avoid
+    /// skipping past the bar boundary.
+    pub fn ticks_to_recall(&self, seq: &SeqN<OUTPUTS, STEPS>) -> Option<u32> {
+        self.pending.map(|_| {
+            let bar = seq.resolution() * BEATS_PER_BAR;
+            (bar - seq.count() % bar) % bar
+        })
+    }
+
+    /// Call before every `Seq::tick`. Loads the pending scene when the
+    /// upcoming tick starts a bar and returns its index.
+    pub fn tick(&mut self, seq: &mut SeqN<OUTPUTS, STEPS>) -> Option<usize> {
+        if self.ticks_to_recall(seq) != Option::Some(0) {
+            return Option::None
+        }
+
+        let index = self.pending.take()?;
+        if let Option::Some(preset) = self.scene(index) {
+            seq.load_preset(preset);
+        }
+
+        Option::Some(index)
+    }
+}
+
+/// The value `percent` of the way from `from` to `to`, rounded to the
+/// nearest.
+fn lerp(from: u32, to: u32, percent: u32) -> u32 {
+    let from = from as i64;
+    let delta = (to as i64 - from) * percent as i64;
+
+    (from + (delta + delta.signum() * 50) / 100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{ConfigN, Density, Frac, Length, Rate};
+
+    fn seq() -> SeqN<2, 8> {
+        let mut configs: Vec<ConfigN<8>, 2> = Vec::new();
+        configs.resize_default(2).ok();
+
+        SeqN::new(120, configs)
+    }
+
+    #[test]
+    fn it_saves_and_recalls_a_scene() {
+        let mut bank: PresetBank<4, 2, 8> = PresetBank::new();
+        let mut seq = seq();
+        bank.save_scene(1, &seq);
+        let saved = seq.preset();
+
+        seq.set_bpm(90);
+        seq.set_rate(0, Rate::Div(2, Frac::Zero));
+
+        assert!(bank.recall_scene(1, &mut seq, Recall::Immediate));
+        assert_eq!(saved, seq.preset());
+    }
+
+    #[test]
+    fn it_ignores_an_empty_scene() {
+        let mut bank: PresetBank<4, 2, 8> = PresetBank::new();
+        let mut seq = seq();
+
+        assert!(!bank.recall_scene(0, &mut seq, Recall::NextBar));
+        assert_eq!(Option::None, bank.pending());
+    }
+
+    #[test]
+    fn it_waits_for_the_next_bar() {
+        let mut bank: PresetBank<4, 2, 8> = PresetBank::new();
+        let mut seq = seq();
+        let bar = seq.resolution() * BEATS_PER_BAR;
+        seq.set_sequence(0, Length(4), Density(2));
+        bank.save_scene(0, &seq);
+        seq.set_sequence(0, Length(8), Density(2));
+        let playing = seq.preset();
+
+        bank.tick(&mut seq);
+        seq.tick();
+        bank.recall_scene(0, &mut seq, Recall::NextBar);
+
+        assert_eq!(Option::Some(bar - 1), bank.ticks_to_recall(&seq));
+
+        for _ in 1..bar {
+            assert_eq!(Option::None, bank.tick(&mut seq));
+            assert_eq!(playing, seq.preset());
+            seq.tick();
+        }
+
+        assert_eq!(Option::Some(0), bank.tick(&mut seq));
+        assert_eq!(bank.scene(0), Option::Some(&seq.preset()));
+        assert_eq!(Option::None, bank.pending());
+    }
+
+    #[test]
+    fn it_morphs_between_two_scenes() {
+        let mut bank: PresetBank<4, 2, 8> = PresetBank::new();
+        let mut seq = seq();
+        seq.set_sequence(0, Length(4), Density(1));
+        seq.set_prob(0, Prob::P20);
+        seq.set_pwm(0, Pwm::P10);
+        bank.save_scene(0, &seq);
+        seq.set_bpm(140);
+        seq.set_sequence(0, Length(8), Density(5));
+        seq.set_prob(0, Prob::P100);
+        seq.set_pwm(0, Pwm::P90);
+        bank.save_scene(1, &seq);
+
+        assert!(bank.morph_scenes(0, 1, 50, &mut seq));
+        let preset = seq.preset();
+        assert_eq!(Bpm::new(130), preset.bpm);
+        assert_eq!(Length(6), preset.configs[0].length());
+        assert_eq!(Density(3), preset.configs[0].density());
+        assert_eq!(Prob::P60, preset.configs[0].prob());
+        assert_eq!(Pwm::P50, preset.configs[0].pwm());
+
+        bank.morph_scenes(0, 1, 0, &mut seq);
+        assert_eq!(bank.scene(0), Option::Some(&seq.preset()));
+        bank.morph_scenes(0, 1, 100, &mut seq);
+        assert_eq!(bank.scene(1), Option::Some(&seq.preset()));
+        assert!(!bank.morph_scenes(0, 2, 50, &mut seq));
+    }
+
+    #[test]
+    fn it_replaces_a_pending_recall() {
+        let mut bank: PresetBank<4, 2, 8> = PresetBank::new();
+        let mut seq = seq();
+        bank.save_scene(0, &seq);
+        seq.set_bpm(90);
+        bank.save_scene(1, &seq);
+        seq.tick();
+
+        bank.recall_scene(0, &mut seq, Recall::NextBar);
+        bank.recall_scene(1, &mut seq, Recall::NextBar);
+
+        assert_eq!(Option::Some(1), bank.pending());
+        bank.clear_scene(1);
+        assert_eq!(Option::None, bank.pending());
+    }
+}
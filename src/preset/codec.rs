@@ -0,0 +1,778 @@
+use super::PresetError;
+use crate::output::{
+    AccentN, ConfigN, Density, Envelope, EnvelopeTime, Frac, Length, Logic, LogicOp, Note,
+    OutputType, Prob, Pwm, Quantizer, Ratchet, Rate, Rotation, Scale, Swing, Waveform,
+};
+
+pub(crate) struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn u8(&mut self, val: u8) -> Result<(), PresetError> {
+        let byte = self
+            .buf
+            .get_mut(self.len)
+            .ok_or(PresetError::BufferTooSmall)?;
+        *byte = val;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn u16(&mut self, val: u16) -> Result<(), PresetError> {
+        val.to_le_bytes().iter().try_for_each(|&byte| self.u8(byte))
+    }
+
+    pub fn u32(&mut self, val: u32) -> Result<(), PresetError> {
+        val.to_le_bytes().iter().try_for_each(|&byte| self.u8(byte))
+    }
+
+    pub fn set_u16(&mut self, at: usize, val: u16) {
+        self.buf[at..at + 2].copy_from_slice(&val.to_le_bytes());
+    }
+
+    /// Writes a record prefixed with its length, so decoders can skip
+    /// fields appended by later versions.
+    pub fn record(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), PresetError>,
+    ) -> Result<(), PresetError> {
+        let at = self.len;
+        self.u16(0)?;
+        f(self)?;
+        let len = self.len - at - 2;
+        self.set_u16(
+            at,
+            u16::try_from(len).map_err(|_| PresetError::BufferTooSmall)?,
+        );
+        Ok(())
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], PresetError> {
+        if len > self.buf.len() {
+            return Err(PresetError::Truncated)
+        }
+
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, PresetError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, PresetError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, PresetError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Reads a length prefixed record written by `Writer::record`.
+    pub fn record(&mut self) -> Result<Reader<'a>, PresetError> {
+        let len = self.u16()? as usize;
+        Ok(Reader::new(self.bytes(len)?))
+    }
+}
+
+pub(crate) trait Encode {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError>;
+}
+
+pub(crate) trait Decode: Sized {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError>;
+}
+
+impl Encode for OutputType {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(match self {
+            OutputType::Gate => 0,
+            OutputType::Euclid => 1,
+            OutputType::Pitch => 2,
+            OutputType::Lfo => 3,
+            OutputType::Envelope => 4,
+            OutputType::Turing => 5,
+            OutputType::Logic => 6,
+        })
+    }
+}
+
+impl Decode for OutputType {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            0 => Ok(OutputType::Gate),
+            1 => Ok(OutputType::Euclid),
+            2 => Ok(OutputType::Pitch),
+            3 => Ok(OutputType::Lfo),
+            4 => Ok(OutputType::Envelope),
+            5 => Ok(OutputType::Turing),
+            6 => Ok(OutputType::Logic),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Prob {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(u32::from(*self) as u8)
+    }
+}
+
+impl Decode for Prob {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            1 => Ok(Prob::P10),
+            2 => Ok(Prob::P20),
+            3 => Ok(Prob::P30),
+            4 => Ok(Prob::P40),
+            5 => Ok(Prob::P50),
+            6 => Ok(Prob::P60),
+            7 => Ok(Prob::P70),
+            8 => Ok(Prob::P80),
+            9 => Ok(Prob::P90),
+            10 => Ok(Prob::P100),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Pwm {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(self.index() as u8)
+    }
+}
+
+impl Decode for Pwm {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            0 => Ok(Pwm::Pew),
+            1 => Ok(Pwm::P10),
+            2 => Ok(Pwm::P20),
+            3 => Ok(Pwm::P30),
+            4 => Ok(Pwm::P40),
+            5 => Ok(Pwm::P50),
+            6 => Ok(Pwm::P60),
+            7 => Ok(Pwm::P70),
+            8 => Ok(Pwm::P80),
+            9 => Ok(Pwm::P90),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Frac {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(match self {
+            Frac::Zero => 0,
+            Frac::OneThird => 1,
+            Frac::OneHalf => 2,
+            Frac::TwoThirds => 3,
+        })
+    }
+}
+
+impl Decode for Frac {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            0 => Ok(Frac::Zero),
+            1 => Ok(Frac::OneThird),
+            2 => Ok(Frac::OneHalf),
+            3 => Ok(Frac::TwoThirds),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Rate {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        match playable(*self)? {
+            Rate::Div(div, frac) => {
+                writer.u8(0)?;
+                writer.u32(div)?;
+                frac.encode(writer)
+            }
+            Rate::Unity => writer.u8(1),
+            Rate::Mult(mult, frac) => {
+                writer.u8(2)?;
+                writer.u32(mult)?;
+                frac.encode(writer)
+            }
+            Rate::Ratio(num, den) => {
+                writer.u8(3)?;
+                writer.u32(num)?;
+                writer.u32(den)
+            }
+        }
+    }
+}
+
+impl Decode for Rate {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let rate = match reader.u8()? {
+            0 => Rate::Div(reader.u32()?, Frac::decode(reader)?),
+            1 => Rate::Unity,
+            2 => Rate::Mult(reader.u32()?, Frac::decode(reader)?),
+            3 => Rate::Ratio(reader.u32()?, reader.u32()?),
+            _ => return Err(PresetError::Invalid),
+        };
+
+        playable(rate)
+    }
+}
+
+/// Rates with steps of no length, or of a length divided by zero, are
+/// rejected both ways so every preset written loads again.
+fn playable(rate: Rate) -> Result<Rate, PresetError> {
+    match rate {
+        Rate::Div(0, Frac::Zero) | Rate::Mult(0, _) | Rate::Ratio(0, _) | Rate::Ratio(_, 0) => {
+            Err(PresetError::Invalid)
+        }
+        rate => Ok(rate),
+    }
+}
+
+impl Encode for Swing {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(u32::from(*self) as u8)
+    }
+}
+
+impl Decode for Swing {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            50 => Ok(Swing::P50),
+            54 => Ok(Swing::P54),
+            58 => Ok(Swing::P58),
+            62 => Ok(Swing::P62),
+            66 => Ok(Swing::P66),
+            71 => Ok(Swing::P71),
+            75 => Ok(Swing::P75),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Ratchet {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(u32::from(*self) as u8)
+    }
+}
+
+impl Decode for Ratchet {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            1 => Ok(Ratchet::R1),
+            2 => Ok(Ratchet::R2),
+            3 => Ok(Ratchet::R3),
+            4 => Ok(Ratchet::R4),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+/// Length, density and rotation of a Euclidean pattern of at most `STEPS`
+/// steps.
+fn encode_pattern(
+    writer: &mut Writer,
+    length: Length,
+    density: Density,
+    rotation: Rotation,
+) -> Result<(), PresetError> {
+    writer.u16(narrow(length.0)?)?;
+    writer.u16(narrow(density.0)?)?;
+    writer.u16(narrow(rotation.0)?)
+}
+
+fn decode_pattern<const STEPS: usize>(
+    reader: &mut Reader,
+) -> Result<(Length, Density, Rotation), PresetError> {
+    let length = reader.u16()? as u32;
+    let density = reader.u16()? as u32;
+    let rotation = reader.u16()? as u32;
+
+    if length == 0 || length as usize > STEPS || density > length {
+        return Err(PresetError::Invalid)
+    }
+
+    Ok((Length(length), Density(density), Rotation(rotation)))
+}
+
+impl Encode for Note {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(self.octave)?;
+        writer.u8(self.semitone)
+    }
+}
+
+impl Decode for Note {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let octave = reader.u8()?;
+        match reader.u8()? {
+            semitone @ 0..=11 => Ok(Note { octave, semitone }),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Scale {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(match self {
+            Scale::Chromatic => 0,
+            Scale::Major => 1,
+            Scale::Minor => 2,
+            Scale::Dorian => 3,
+            Scale::Phrygian => 4,
+            Scale::Lydian => 5,
+            Scale::Mixolydian => 6,
+            Scale::Locrian => 7,
+            Scale::MajorPentatonic => 8,
+            Scale::MinorPentatonic => 9,
+            Scale::Custom(_) => 10,
+        })?;
+        writer.u16(self.mask())
+    }
+}
+
+impl Decode for Scale {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let scale = match reader.u8()? {
+            0 => Scale::Chromatic,
+            1 => Scale::Major,
+            2 => Scale::Minor,
+            3 => Scale::Dorian,
+            4 => Scale::Phrygian,
+            5 => Scale::Lydian,
+            6 => Scale::Mixolydian,
+            7 => Scale::Locrian,
+            8 => Scale::MajorPentatonic,
+            9 => Scale::MinorPentatonic,
+            10 => Scale::Custom(0),
+            _ => return Err(PresetError::Invalid),
+        };
+        let mask = reader.u16()?;
+
+        match scale {
+            Scale::Custom(_) => Ok(Scale::Custom(mask)),
+            _ => Ok(scale),
+        }
+    }
+}
+
+impl Encode for Quantizer {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        self.scale.encode(writer)?;
+        writer.u8(self.root)
+    }
+}
+
+impl Decode for Quantizer {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let scale = Scale::decode(reader)?;
+        match reader.u8()? {
+            root @ 0..=11 => Ok(Quantizer { scale, root }),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Waveform {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(match self {
+            Waveform::Sine => 0,
+            Waveform::Triangle => 1,
+            Waveform::Saw => 2,
+            Waveform::Square => 3,
+            Waveform::SampleAndHold => 4,
+        })
+    }
+}
+
+impl Decode for Waveform {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            0 => Ok(Waveform::Sine),
+            1 => Ok(Waveform::Triangle),
+            2 => Ok(Waveform::Saw),
+            3 => Ok(Waveform::Square),
+            4 => Ok(Waveform::SampleAndHold),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for EnvelopeTime {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        match self {
+            EnvelopeTime::Ticks(ticks) => {
+                writer.u8(0)?;
+                writer.u32(*ticks)
+            }
+            EnvelopeTime::StepPercent(percent) => {
+                writer.u8(1)?;
+                writer.u32(*percent)
+            }
+        }
+    }
+}
+
+impl Decode for EnvelopeTime {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        match reader.u8()? {
+            0 => Ok(EnvelopeTime::Ticks(reader.u32()?)),
+            1 => Ok(EnvelopeTime::StepPercent(reader.u32()?)),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+// Both kinds are stored at the same length, the sustain and release of an
+// AD envelope being ignored.
+impl Encode for Envelope {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        let (kind, attack, decay, sustain, release) = match *self {
+            Envelope::Ad { attack, decay } => (0, attack, decay, 0, EnvelopeTime::Ticks(0)),
+            Envelope::Adsr {
+                attack,
+                decay,
+                sustain,
+                release,
+            } => (1, attack, decay, sustain, release),
+        };
+
+        writer.u8(kind)?;
+        attack.encode(writer)?;
+        decay.encode(writer)?;
+        writer.u16(sustain)?;
+        release.encode(writer)
+    }
+}
+
+impl Decode for Envelope {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let kind = reader.u8()?;
+        let attack = EnvelopeTime::decode(reader)?;
+        let decay = EnvelopeTime::decode(reader)?;
+        let sustain = reader.u16()?;
+        let release = EnvelopeTime::decode(reader)?;
+
+        match kind {
+            0 => Ok(Envelope::Ad { attack, decay }),
+            1 => Ok(Envelope::Adsr {
+                attack,
+                decay,
+                sustain,
+                release,
+            }),
+            _ => Err(PresetError::Invalid),
+        }
+    }
+}
+
+impl Encode for Logic {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.u8(match self.op {
+            LogicOp::And => 0,
+            LogicOp::Or => 1,
+            LogicOp::Xor => 2,
+            LogicOp::Nand => 3,
+            LogicOp::Nor => 4,
+            LogicOp::Not => 5,
+        })?;
+        writer.u8(u8::try_from(self.a).map_err(|_| PresetError::Invalid)?)?;
+        writer.u8(u8::try_from(self.b).map_err(|_| PresetError::Invalid)?)
+    }
+}
+
+impl Decode for Logic {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let op = match reader.u8()? {
+            0 => LogicOp::And,
+            1 => LogicOp::Or,
+            2 => LogicOp::Xor,
+            3 => LogicOp::Nand,
+            4 => LogicOp::Nor,
+            5 => LogicOp::Not,
+            _ => return Err(PresetError::Invalid),
+        };
+        let a = reader.u8()? as usize;
+        let b = reader.u8()? as usize;
+
+        Ok(Logic::new(op, a, b))
+    }
+}
+
+fn narrow(val: u32) -> Result<u16, PresetError> {
+    u16::try_from(val).map_err(|_| PresetError::Invalid)
+}
+
+/// Bytes taken by the length prefix of a record.
+pub(crate) const RECORD_LEN: usize = 2;
+
+// Largest encoded size of each field of a config.
+const OUTPUT_TYPE_LEN: usize = 1;
+const PATTERN_LEN: usize = 6;
+const PROB_LEN: usize = 1;
+const PWM_LEN: usize = 1;
+const RATE_LEN: usize = 9;
+const SWING_LEN: usize = 1;
+const STEP_COUNT_LEN: usize = 2;
+const QUANTIZER_LEN: usize = 4;
+const WAVEFORM_LEN: usize = 1;
+const ENVELOPE_LEN: usize = 18;
+const LOGIC_LEN: usize = 3;
+const RATCHET_LEN: usize = 1;
+const NOTE_LEN: usize = 2;
+const TIE_LEN: usize = 1;
+
+/// Largest encoded size of a config, leaving out its steps.
+pub(crate) const CONFIG_LEN: usize = RECORD_LEN
+    + OUTPUT_TYPE_LEN
+    + PATTERN_LEN
+    + PROB_LEN
+    + PWM_LEN
+    + RATE_LEN
+    + SWING_LEN
+    + PATTERN_LEN
+    + STEP_COUNT_LEN
+    + QUANTIZER_LEN
+    + WAVEFORM_LEN
+    + ENVELOPE_LEN
+    + LOGIC_LEN;
+
+/// Encoded size of each step of a config.
+pub(crate) const STEP_LEN: usize = RATCHET_LEN + NOTE_LEN + TIE_LEN;
+
+// Version 1 layout: output type, pattern, prob, pwm, rate, swing, accent
+// pattern, then one ratchet per step. `on` of each step is derived from
+// the patterns so it is not stored.
+//
+// Version 2 appends note and tie of each step.
+//
+// Version 3 appends the quantizer as scale, scale mask and root.
+//
+// Version 4 appends the LFO waveform.
+//
+// Version 5 appends the envelope.
+//
+// Version 6 appends the logic operator and inputs.
+impl<const STEPS: usize> Encode for ConfigN<STEPS> {
+    fn encode(&self, writer: &mut Writer) -> Result<(), PresetError> {
+        writer.record(|writer| {
+            self.output_type().encode(writer)?;
+            encode_pattern(writer, self.length(), self.density(), self.rotation())?;
+            self.prob().encode(writer)?;
+            self.pwm().encode(writer)?;
+            self.rate().encode(writer)?;
+            self.swing().encode(writer)?;
+            let accent: &AccentN<STEPS> = self.accent();
+            encode_pattern(writer, accent.length(), accent.density(), accent.rotation())?;
+            writer.u16(self.sequence().len() as u16)?;
+            self.sequence()
+                .iter()
+                .try_for_each(|step| step.ratchet.encode(writer))?;
+            self.sequence().iter().try_for_each(|step| {
+                step.note.encode(writer)?;
+                writer.u8(step.tie as u8)
+            })?;
+            self.quantizer().encode(writer)?;
+            self.waveform().encode(writer)?;
+            self.envelope().encode(writer)?;
+            self.logic().encode(writer)
+        })
+    }
+}
+
+impl<const STEPS: usize> Decode for ConfigN<STEPS> {
+    fn decode(reader: &mut Reader) -> Result<Self, PresetError> {
+        let mut reader = reader.record()?;
+        let mut config: ConfigN<STEPS> = Default::default();
+
+        let output_type = OutputType::decode(&mut reader)?;
+        let (length, density, rotation) = decode_pattern::<STEPS>(&mut reader)?;
+        let prob = Prob::decode(&mut reader)?;
+        let pwm = Pwm::decode(&mut reader)?;
+        let rate = Rate::decode(&mut reader)?;
+        let swing = Swing::decode(&mut reader)?;
+        let (accent_length, accent_density, accent_rotation) =
+            decode_pattern::<STEPS>(&mut reader)?;
+
+        config.set_rotation(rotation);
+        config.set_sequence(length, density);
+        config.set_output_type(output_type);
+        config.set_prob(prob);
+        config.set_pwm(pwm);
+        config.set_rate(rate);
+        config.set_swing(swing);
+        config.set_accent(accent_length, accent_density, accent_rotation);
+
+        let steps = reader.u16()? as usize;
+        for step in 0..steps {
+            let ratchet = Ratchet::decode(&mut reader)?;
+            config.set_ratchet(step, ratchet);
+        }
+
+        if reader.is_empty() {
+            return Ok(config)
+        }
+
+        for step in 0..steps {
+            let note = Note::decode(&mut reader)?;
+            let tie = match reader.u8()? {
+                0 => false,
+                1 => true,
+                _ => return Err(PresetError::Invalid),
+            };
+            config.set_note(step, note);
+            config.set_tie(step, tie);
+        }
+
+        if reader.is_empty() {
+            return Ok(config)
+        }
+
+        config.set_quantizer(Quantizer::decode(&mut reader)?);
+
+        if reader.is_empty() {
+            return Ok(config)
+        }
+
+        config.set_waveform(Waveform::decode(&mut reader)?);
+
+        if reader.is_empty() {
+            return Ok(config)
+        }
+
+        config.set_envelope(Envelope::decode(&mut reader)?);
+
+        if reader.is_empty() {
+            return Ok(config)
+        }
+
+        config.set_logic(Logic::decode(&mut reader)?);
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_a_rate() {
+        let mut buf = [0; 16];
+        let mut writer = Writer::new(&mut buf);
+        Rate::Div(5, Frac::OneThird).encode(&mut writer).unwrap();
+        Rate::Unity.encode(&mut writer).unwrap();
+        Rate::Ratio(7, 8).encode(&mut writer).unwrap();
+
+        assert_eq!(16, writer.len());
+
+        let mut reader = Reader::new(&buf);
+        assert_eq!(Ok(Rate::Div(5, Frac::OneThird)), Rate::decode(&mut reader));
+        assert_eq!(Ok(Rate::Unity), Rate::decode(&mut reader));
+        assert_eq!(Ok(Rate::Ratio(7, 8)), Rate::decode(&mut reader));
+    }
+
+    #[test]
+    fn it_round_trips_a_rate_of_a_fraction_of_a_beat() {
+        let mut buf = [0; 6];
+        let mut writer = Writer::new(&mut buf);
+        Rate::Div(0, Frac::OneHalf).encode(&mut writer).unwrap();
+
+        let mut reader = Reader::new(&buf);
+        assert_eq!(Ok(Rate::Div(0, Frac::OneHalf)), Rate::decode(&mut reader));
+    }
+
+    #[test]
+    fn it_fails_to_encode_a_rate_of_no_length() {
+        let mut buf = [0; 9];
+        let mut writer = Writer::new(&mut buf);
+
+        assert_eq!(
+            Err(PresetError::Invalid),
+            Rate::Div(0, Frac::Zero).encode(&mut writer)
+        );
+        assert_eq!(
+            Err(PresetError::Invalid),
+            Rate::Ratio(3, 0).encode(&mut writer)
+        );
+    }
+
+    #[test]
+    fn it_skips_fields_appended_to_a_record() {
+        let mut buf = [0; 8];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .record(|writer| {
+                writer.u8(1)?;
+                writer.u8(2)
+            })
+            .unwrap();
+        writer.u8(3).unwrap();
+
+        let mut reader = Reader::new(&buf[..5]);
+        let mut record = reader.record().unwrap();
+        assert_eq!(Ok(1), record.u8());
+        assert_eq!(Ok(3), reader.u8());
+        assert_eq!(Err(PresetError::Truncated), reader.u8());
+    }
+
+    #[test]
+    fn it_rejects_a_pattern_longer_than_the_max_steps() {
+        let mut buf = [0; 6];
+        let mut writer = Writer::new(&mut buf);
+        encode_pattern(&mut writer, Length(17), Density(4), Rotation(0)).unwrap();
+
+        let mut reader = Reader::new(&buf);
+        assert_eq!(Err(PresetError::Invalid), decode_pattern::<16>(&mut reader));
+    }
+
+    #[test]
+    fn it_fails_to_encode_a_pattern_out_of_range() {
+        let mut buf = [0; 6];
+        let mut writer = Writer::new(&mut buf);
+
+        assert_eq!(
+            Err(PresetError::Invalid),
+            encode_pattern(&mut writer, Length(16), Density(4), Rotation(70_000))
+        );
+    }
+
+    #[test]
+    fn it_fails_to_write_past_the_end_of_the_buffer() {
+        let mut buf = [0; 3];
+        let mut writer = Writer::new(&mut buf);
+
+        assert_eq!(Err(PresetError::BufferTooSmall), writer.u32(1));
+    }
+}
@@ -1,42 +1,92 @@
 use heapless::Vec;
 
 use super::{
+    bpm::Bpm,
+    clock::{Clock, ClockSource},
+    event::Event,
+    midi::{MidiMessage, MidiNote, MidiOut, MidiParser, MIDI_BEATS_PER_QUARTER},
     output::*,
+    preset::{SeqPresetN, BEATS_PER_BAR},
     tick::{Tick, RESOLUTION},
+    transport::{Transport, TransportEvent},
 };
 
-pub struct Seq {
+/// A sequencer of the default size, see `SeqN`.
+pub type Seq = SeqN<MAX_OUTPUTS, MAX_STEPS>;
+
+pub struct SeqN<const OUTPUTS: usize, const STEPS: usize> {
+    clock: Clock,
+    loop_events: bool,
+    midi_in: MidiParser,
+    midi_notes: Vec<Option<MidiNote>, OUTPUTS>,
+    midi_out: MidiOut,
     tick: Tick,
-    outputs: Vec<Output, 4>,
-    output_states: OutputStates,
+    outputs: Vec<OutputN<STEPS>, OUTPUTS>,
+    output_states: OutputStatesN<OUTPUTS>,
+    /// Rate changes waiting for the count they are aligned to.
+    pending_rates: Vec<Option<(Rate, u32)>, OUTPUTS>,
+    rate_align: RateAlign,
+    /// Set by a stop or pause so the next tick forces the gates low even if
+    /// another transport command follows first.
+    release_pending: bool,
     resolution: u32,
+    transport: Transport,
+    transport_change: Option<TransportEvent>,
+    transport_event: Option<TransportEvent>,
 }
 
-impl Default for Seq {
+/// How far ahead `Seq::next_event` looks for a change, in beats.
+const EVENT_HORIZON_BEATS: u32 = 4;
+
+impl<const OUTPUTS: usize, const STEPS: usize> Default for SeqN<OUTPUTS, STEPS> {
     fn default() -> Self {
         Self::new(120, Default::default())
     }
 }
 
-impl Seq {
-    pub fn new(bpm: u32, configs: Vec<Config, 4>) -> Self {
-        Seq::new_with_resolution(RESOLUTION, bpm, configs)
+impl<const OUTPUTS: usize, const STEPS: usize> SeqN<OUTPUTS, STEPS> {
+    pub fn new(bpm: u32, configs: Vec<ConfigN<STEPS>, OUTPUTS>) -> Self {
+        Self::with_tempo(Bpm::new(bpm), configs)
+    }
+
+    /// Like `new`, with a tempo in hundredths of a BPM.
+    pub fn with_tempo(tempo: Bpm, configs: Vec<ConfigN<STEPS>, OUTPUTS>) -> Self {
+        Self::new_with_resolution(RESOLUTION, tempo, configs)
     }
 
-    fn new_with_resolution(resolution: u32, bpm: u32, configs: Vec<Config, 4>) -> Self {
-        let tick = Tick::new(bpm);
-        let mut output_states = Vec::new();
-        output_states.resize_default(configs.len()).ok();
+    fn new_with_resolution(
+        resolution: u32,
+        tempo: Bpm,
+        configs: Vec<ConfigN<STEPS>, OUTPUTS>,
+    ) -> Self {
+        let tick = Tick::with_tempo(tempo);
+        let output_states = (0..configs.len())
+            .map(|index| OutputState {
+                rng: Rng::with_seed(RNG_SEED, index as u64),
+                ..Default::default()
+            })
+            .collect();
         let outputs = configs
             .iter()
-            .map(|config| Output::new(resolution, &tick, config.clone()))
+            .map(|config| OutputN::new(resolution, &tick, config.clone()))
             .collect();
 
         Self {
+            clock: Default::default(),
+            loop_events: false,
+            midi_in: Default::default(),
+            midi_notes: configs.iter().map(|_| Option::None).collect(),
+            midi_out: Default::default(),
             tick,
             outputs,
             output_states,
+            pending_rates: configs.iter().map(|_| Option::None).collect(),
+            rate_align: Default::default(),
+            release_pending: false,
             resolution,
+            transport: Default::default(),
+            transport_change: Option::None,
+            transport_event: Option::None,
         }
     }
 
@@ -44,14 +94,537 @@ impl Seq {
         self.tick.duration_micros
     }
 
+    /// Time of the tick at `count` in microseconds since the sequence was
+    /// last started, reset or relocated. Scheduling each tick at this time,
+    /// rather than every `tick_duration_micros`, keeps the average period
+    /// exact so the tempo doesn't drift over long runs.
+    pub fn tick_micros(&self, count: u32) -> u64 {
+        self.tick.micros_at(count)
+    }
+
     pub fn tick(&mut self) {
+        self.update();
+        self.send_notes();
+    }
+
+    fn update(&mut self) {
+        self.transport_change = self.transport_event.take();
+
+        let release = core::mem::take(&mut self.release_pending);
+        if release {
+            self.release_gates();
+        }
+
+        if !self.transport.is_running() {
+            if !release {
+                self.clear_changes();
+            }
+            return
+        }
+
+        // Running again before the release was processed: send the note offs
+        // now, the outputs work out their own changes below.
+        if release {
+            self.send_notes();
+        }
+
+        if self.clock.hold(self.tick.count, self.resolution) {
+            self.clear_changes();
+            return
+        }
+
+        self.advance();
+    }
+
+    /// Registers an external clock edge received at `micros`.
+    ///
+    /// The tempo is estimated from the interval to the previous edge and any
+    /// ticks the internal clock fell behind are caught up so outputs stay
+    /// phase-locked to the incoming clock. Ignored on the internal clock and
+    /// while the transport is not running.
+    pub fn clock(&mut self, micros: u64) {
+        let ppqn = match self.clock.ppqn() {
+            Option::Some(ppqn) if self.transport.is_running() => ppqn,
+            _ => return,
+        };
+
+        if let Option::Some(interval) = self.clock.edge(micros, self.tick.count, self.resolution) {
+            let ticks_per_pulse = self.clock.ticks_per_pulse(self.resolution);
+            self.tick
+                .set_pulse_interval(interval, ticks_per_pulse, ppqn.0);
+            self.update_outputs();
+        }
+
+        let pulse_count = self.clock.pulse_count(self.resolution).unwrap();
+        if self.tick.count > pulse_count {
+            return
+        }
+
+        let initial: Vec<(bool, usize), OUTPUTS> = self
+            .output_states
+            .iter()
+            .map(|state| (state.on, state.index))
+            .collect();
+
+        while self.tick.count <= pulse_count {
+            self.advance();
+            self.send_notes();
+        }
+
+        for (state, (on, index)) in self.output_states.iter_mut().zip(initial) {
+            state.on_change = on != state.on;
+            state.index_change = index != state.index;
+        }
+    }
+
+    /// Feeds a byte of a MIDI stream received at `micros`. Start, stop and
+    /// continue drive the transport and song position moves the position
+    /// while stopped. Clock pulses are followed when the clock source is
+    /// `ClockSource::External(Ppqn(MIDI_PPQN))`.
+    pub fn receive_midi(&mut self, byte: u8, micros: u64) {
+        match self.midi_in.parse(byte) {
+            Option::Some(MidiMessage::Clock) => self.clock(micros),
+            Option::Some(MidiMessage::Start) => self.start(),
+            Option::Some(MidiMessage::Continue) => self.resume(),
+            Option::Some(MidiMessage::Stop) => self.stop(),
+            Option::Some(MidiMessage::SongPosition(position)) if !self.transport.is_running() => {
+                let ticks_per_beat = self.resolution / MIDI_BEATS_PER_QUARTER;
+                self.locate(position as u32 * ticks_per_beat);
+            }
+            _ => {}
+        }
+    }
+
+    /// Computes the next tick on which any output turns on or off, so
+    /// firmware can sleep a timer until then instead of ticking every
+    /// tick, e.g. until `tick_micros` of the tick `ticks` ahead. Skip `ticks`
+    /// with `skip` and then `tick` to process the event. Logic outputs whose
+    /// inputs or settings changed between ticks follow on the next tick.
+    ///
+    /// Returns `None` when nothing can happen until the transport changes
+    /// or when following an external clock.
+    pub fn next_event(&self) -> Option<Event<OUTPUTS>> {
+        if !self.is_schedulable() || self.outputs.is_empty() {
+            return Option::None
+        }
+
+        let mut outputs = self.outputs.clone();
+        let mut output_states = self.output_states.clone();
+        let mut pending_rates = self.pending_rates.clone();
+        let mut horizon = self.tick.count + self.resolution * EVENT_HORIZON_BEATS;
+        if self.midi_out.is_clock_enabled() {
+            let ticks_per_clock = MidiOut::ticks_per_clock(self.resolution);
+            horizon = horizon.min(self.tick.count.next_multiple_of(ticks_per_clock));
+        }
+        let mut count = self.tick.count;
+
+        let mut logic_states = output_states.clone();
+        Self::update_logic(&outputs, &mut logic_states);
+        let mut logic_edge = if logic_states
+            .iter()
+            .zip(output_states.iter())
+            .any(|(logic, state)| logic.on != state.on)
+        {
+            count
+        } else {
+            u32::MAX
+        };
+
+        loop {
+            let next = outputs
+                .iter()
+                .map(|output| output.next_edge(count))
+                .min()
+                .unwrap()
+                .min(Self::next_rate_change(&pending_rates))
+                .min(core::mem::replace(&mut logic_edge, u32::MAX))
+                .min(horizon);
+            let mut event = Event {
+                ticks: next - self.tick.count,
+                outputs: Vec::new(),
+            };
+
+            if next == horizon {
+                return Option::Some(event)
+            }
+
+            Self::apply_rates(&self.tick, &mut outputs, &mut pending_rates, next);
+            let looping = self.loop_events
+                && outputs.iter().any(|output| {
+                    output.config().output_type() != OutputType::Logic
+                        && output.is_loop_starting(next)
+                });
+            let ticked: Vec<bool, OUTPUTS> = outputs
+                .iter_mut()
+                .zip(output_states.iter_mut())
+                .map(|(output, state)| {
+                    let edge = output.next_edge(next) == next;
+                    if edge {
+                        output.tick(next, state);
+                    }
+                    edge
+                })
+                .collect();
+            Self::update_logic(&outputs, &mut output_states);
+
+            for (index, (output, state)) in outputs.iter().zip(output_states.iter()).enumerate() {
+                let updated = ticked[index] || output.config().output_type() == OutputType::Logic;
+                if updated && state.on_change {
+                    event.outputs.push(index).ok();
+                }
+            }
+
+            if looping || !event.outputs.is_empty() {
+                return Option::Some(event)
+            }
+
+            count = next + 1;
+        }
+    }
+
+    /// Advances `ticks` ticks with the same result as calling `tick` that
+    /// many times, but only processing ticks on which an output may change.
+    ///
+    /// MIDI messages of the skipped ticks are queued as ticking would queue
+    /// them, but the queue only holds 32 bytes and drops what doesn't fit.
+    /// With MIDI out in use, skip no further than `next_event` before
+    /// reading the queue; it stops at every MIDI clock while clock output is
+    /// enabled.
+    pub fn skip(&mut self, ticks: u32) {
+        if ticks == 0 {
+            return
+        }
+
+        self.tick();
+
+        if !self.is_schedulable() {
+            for _ in 1..ticks {
+                self.tick();
+            }
+            return
+        }
+
+        let begin = self.tick.count;
+        let end = begin + ticks - 1;
+
+        // Edges are processed in order across outputs so logic outputs see
+        // their inputs as they are on every tick, and MIDI clocks and notes
+        // are queued in the order ticking would queue them.
+        let mut last: Vec<Option<u32>, OUTPUTS> =
+            self.outputs.iter().map(|_| Option::None).collect();
+        let mut count = begin;
+        loop {
+            let next = self
+                .outputs
+                .iter()
+                .map(|output| output.next_edge(count))
+                .min()
+                .unwrap_or(end)
+                .min(Self::next_rate_change(&self.pending_rates));
+            if next >= end {
+                break
+            }
+
+            self.send_clocks(count, next + 1);
+            self.clear_changes();
+            Self::apply_rates(&self.tick, &mut self.outputs, &mut self.pending_rates, next);
+
+            for ((output, state), last) in self
+                .outputs
+                .iter_mut()
+                .zip(self.output_states.iter_mut())
+                .zip(last.iter_mut())
+            {
+                if output.config().output_type() == OutputType::Logic
+                    || output.next_edge(next) == next
+                {
+                    output.tick(next, state);
+                    *last = Option::Some(next);
+                }
+            }
+            Self::update_logic(&self.outputs, &mut self.output_states);
+            self.send_notes();
+
+            count = next + 1;
+        }
+        self.send_clocks(count, end);
+
+        for (state, last) in self.output_states.iter_mut().zip(last) {
+            if begin < end && last != Option::Some(end - 1) {
+                state.on_change = false;
+                state.index_change = false;
+            }
+        }
+
+        self.tick.count = end;
+    }
+
+    /// Queues the MIDI clocks due on the ticks from `begin` up to `end`.
+    fn send_clocks(&mut self, begin: u32, end: u32) {
+        let ticks_per_clock = MidiOut::ticks_per_clock(self.resolution);
+        for count in
+            (begin.next_multiple_of(ticks_per_clock)..end).step_by(ticks_per_clock as usize)
+        {
+            self.midi_out.clock(count, self.resolution);
+        }
+    }
+
+    fn is_schedulable(&self) -> bool {
+        self.transport.is_running() && self.clock.source() == ClockSource::Internal
+    }
+
+    fn advance(&mut self) {
+        self.midi_out.clock(self.tick.count, self.resolution);
+        Self::apply_rates(
+            &self.tick,
+            &mut self.outputs,
+            &mut self.pending_rates,
+            self.tick.count,
+        );
+
         for (output, state) in self.outputs.iter_mut().zip(self.output_states.iter_mut()) {
             output.tick(self.tick.count, state);
         }
+        Self::update_logic(&self.outputs, &mut self.output_states);
 
         self.tick.count += 1;
     }
 
+    /// Applies the rate changes aligned to `count`.
+    fn apply_rates(
+        tick: &Tick,
+        outputs: &mut [OutputN<STEPS>],
+        pending_rates: &mut [Option<(Rate, u32)>],
+        count: u32,
+    ) {
+        for (output, pending) in outputs.iter_mut().zip(pending_rates.iter_mut()) {
+            if let Option::Some((rate, at)) = *pending {
+                if at == count {
+                    output.align_rate(tick, rate, at);
+                    *pending = Option::None;
+                }
+            }
+        }
+    }
+
+    fn next_rate_change(pending_rates: &[Option<(Rate, u32)>]) -> u32 {
+        pending_rates
+            .iter()
+            .flatten()
+            .map(|(_, at)| *at)
+            .min()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Sets the gates of logic outputs from their inputs. Runs after every
+    /// other output has ticked and in order of index, so a logic output sees
+    /// the gates of the same tick. Inputs that are logic outputs of a higher
+    /// or the same index, or don't exist, read as off.
+    fn update_logic(outputs: &[OutputN<STEPS>], states: &mut [OutputState]) {
+        for (index, output) in outputs.iter().enumerate() {
+            if output.config().output_type() != OutputType::Logic {
+                continue
+            }
+
+            let input = |input: usize| {
+                outputs.get(input).is_some_and(|output| {
+                    input < index || output.config().output_type() != OutputType::Logic
+                }) && states[input].on
+            };
+            let logic = output.config().logic();
+            let on = logic.eval(input(logic.a), input(logic.b));
+
+            let state = &mut states[index];
+            state.on_change = state.on != on;
+            state.on = on;
+        }
+    }
+
+    /// Rewinds and runs from the first step.
+    pub fn start(&mut self) {
+        self.transport_command(TransportEvent::Start);
+    }
+
+    /// Halts and forces all gates low. Position is kept so the sequence can
+    /// be continued with `resume`.
+    pub fn stop(&mut self) {
+        self.transport_command(TransportEvent::Stop);
+    }
+
+    /// Halts and forces all gates low, keeping position.
+    pub fn pause(&mut self) {
+        self.transport_command(TransportEvent::Pause);
+    }
+
+    /// Continues from the current position after a stop or pause.
+    pub fn resume(&mut self) {
+        self.transport_command(TransportEvent::Continue);
+    }
+
+    /// Rewinds to before the first step without changing the transport
+    /// state.
+    pub fn reset(&mut self) {
+        self.transport_command(TransportEvent::Reset);
+    }
+
+    fn transport_command(&mut self, event: TransportEvent) {
+        let transport = match self.transport.next(event) {
+            Option::Some(transport) => transport,
+            Option::None => return,
+        };
+
+        if matches!(event, TransportEvent::Start | TransportEvent::Reset) {
+            self.rewind();
+        }
+        if matches!(
+            event,
+            TransportEvent::Start | TransportEvent::Continue | TransportEvent::Reset
+        ) {
+            self.clock.reset();
+        }
+
+        self.transport = transport;
+        self.transport_event = Option::Some(event);
+        self.release_pending |= matches!(event, TransportEvent::Stop | TransportEvent::Pause);
+        self.midi_out
+            .transport(event, self.transport, self.tick.count, self.resolution);
+    }
+
+    /// Moves to `count` as if the sequence had played up to it, with all
+    /// gates low.
+    /// Locked outputs replay from the start of their current loop, so they
+    /// keep playing the same loop from the new position.
+    fn locate(&mut self, count: u32) {
+        self.rewind();
+
+        for (output, state) in self.outputs.iter_mut().zip(self.output_states.iter_mut()) {
+            state.rng.restart_loop();
+            if state.rng.is_locked() {
+                state.register = state.loop_register;
+            }
+
+            let mut next = output.next_edge(0);
+            while next < count {
+                output.tick(next, state);
+                next = output.next_edge(next + 1);
+            }
+        }
+
+        for state in self.output_states.iter_mut() {
+            state.accent = false;
+            state.on = false;
+            state.envelope = Default::default();
+        }
+        Self::update_logic(&self.outputs, &mut self.output_states);
+
+        self.tick.set_count(count);
+        self.clear_changes();
+        self.clock.reset();
+    }
+
+    /// Envelopes start over and gates still high are forced low, with their
+    /// MIDI notes released, so the first hit after the rewind triggers
+    /// again. A pending release forces them low on the next tick instead.
+    fn rewind(&mut self) {
+        self.tick.set_count(0);
+
+        for (state, note) in self.output_states.iter_mut().zip(self.midi_notes.iter()) {
+            state.envelope = Default::default();
+            if self.release_pending || !state.on {
+                continue
+            }
+            if let Option::Some(note) = note {
+                self.midi_out.note(note, false, false);
+            }
+            state.accent = false;
+            state.on = false;
+        }
+
+        for (output, pending) in self.outputs.iter_mut().zip(self.pending_rates.iter_mut()) {
+            let rate = pending
+                .take()
+                .map_or(output.config().rate(), |(rate, _)| rate);
+            output.align_rate(&self.tick, rate, 0);
+            output.reset();
+        }
+    }
+
+    fn send_notes(&mut self) {
+        for (note, state) in self.midi_notes.iter().zip(self.output_states.iter()) {
+            if let (Option::Some(note), true) = (note, state.on_change) {
+                self.midi_out.note(note, state.on, state.accent);
+            }
+        }
+    }
+
+    fn release_gates(&mut self) {
+        for state in self.output_states.iter_mut() {
+            state.envelope.release(self.tick.count);
+            state.on_change = state.on;
+            state.on = false;
+            state.index_change = false;
+        }
+    }
+
+    fn clear_changes(&mut self) {
+        for state in self.output_states.iter_mut() {
+            state.on_change = false;
+            state.index_change = false;
+        }
+    }
+
+    fn update_outputs(&mut self) {
+        for output in self.outputs.iter_mut() {
+            output.set_tick(&self.tick);
+        }
+    }
+
+    /// Tempo rounded to whole BPM.
+    pub fn bpm(&self) -> u32 {
+        self.tick.bpm
+    }
+
+    pub fn tempo(&self) -> Bpm {
+        self.tick.tempo
+    }
+
+    /// The tick that will be processed next.
+    pub fn count(&self) -> u32 {
+        self.tick.count
+    }
+
+    pub fn clock_source(&self) -> ClockSource {
+        self.clock.source()
+    }
+
+    pub fn is_clock_dropped(&self) -> bool {
+        self.clock.is_dropped()
+    }
+
+    pub fn get_output_type(&self, index: usize) -> OutputType {
+        self.outputs[index].config().output_type()
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn get_accent(&self, index: usize) -> bool {
+        self.output_states[index].accent
+    }
+
+    /// Quantized pitch of the output in millivolts on a 1V/oct scale.
+    pub fn get_cv(&self, index: usize) -> u32 {
+        self.output_states[index].note.millivolts()
+    }
+
+    /// Value of the output on the full scale of a `u16`, e.g. the level of
+    /// an LFO or envelope, for driving a DAC.
+    pub fn get_value(&self, index: usize) -> u16 {
+        let count = self.tick.count.saturating_sub(1);
+        self.outputs[index].value(count, &self.output_states[index])
+    }
+
     pub fn get_index(&self, index: usize) -> usize {
         self.output_states[index].index
     }
@@ -60,6 +633,16 @@ impl Seq {
         self.output_states[index].index_change
     }
 
+    /// Whether the next tick starts the output's sequence from its first
+    /// step.
+    pub fn is_loop_starting(&self, index: usize) -> bool {
+        self.outputs[index].is_loop_starting(self.tick.count)
+    }
+
+    pub fn get_note(&self, index: usize) -> Note {
+        self.output_states[index].note
+    }
+
     pub fn get_on(&self, index: usize) -> bool {
         self.output_states[index].on
     }
@@ -68,12 +651,111 @@ impl Seq {
         self.output_states[index].on_change
     }
 
+    pub fn get_transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// The transport event that took effect on the last tick, if any.
+    pub fn get_transport_change(&self) -> Option<TransportEvent> {
+        self.transport_change
+    }
+
+    pub fn preset(&self) -> SeqPresetN<OUTPUTS, STEPS> {
+        SeqPresetN {
+            bpm: self.tick.tempo,
+            configs: self
+                .outputs
+                .iter()
+                .map(|output| output.config().clone())
+                .collect(),
+        }
+    }
+
+    /// Restores bpm and the config of each output in the preset. Outputs
+    /// keep their position in their sequence.
+    pub fn load_preset(&mut self, preset: &SeqPresetN<OUTPUTS, STEPS>) {
+        self.set_tempo(preset.bpm);
+
+        for (output, config) in self.outputs.iter_mut().zip(preset.configs.iter()) {
+            output.set_config(&self.tick, config.clone());
+        }
+        for pending in self.pending_rates.iter_mut() {
+            *pending = Option::None;
+        }
+    }
+
+    /// Replaces the config of an output and plays it from the first step on
+    /// the next tick it starts a step.
+    pub fn load_pattern(&mut self, index: usize, config: ConfigN<STEPS>) {
+        let output = &mut self.outputs[index];
+        output.set_config(&self.tick, config);
+        output.reset();
+        self.pending_rates[index] = Option::None;
+    }
+
+    /// Next byte of MIDI clock, transport and note messages to send. Drain
+    /// after every tick.
+    pub fn read_midi(&mut self) -> Option<u8> {
+        self.midi_out.pop()
+    }
+
     pub fn resolution(&self) -> u32 {
         self.resolution
     }
 
+    pub fn set_accent(
+        &mut self,
+        index: usize,
+        length: Length,
+        density: Density,
+        rotation: Rotation,
+    ) {
+        self.outputs[index].set_accent(length, density, rotation);
+    }
+
     pub fn set_bpm(&mut self, bpm: u32) {
-        self.tick.set_bpm(bpm);
+        self.set_tempo(Bpm::new(bpm));
+    }
+
+    pub fn set_tempo(&mut self, tempo: Bpm) {
+        self.tick.set_tempo(tempo);
+        self.update_outputs();
+    }
+
+    pub fn set_clock_source(&mut self, source: ClockSource) {
+        self.clock = Clock::new(source);
+    }
+
+    /// Makes `next_event` also stop on every tick on which an output starts
+    /// its sequence, so a `Song` can switch patterns at loop boundaries.
+    pub fn set_loop_events(&mut self, enabled: bool) {
+        self.loop_events = enabled;
+    }
+
+    /// Emits MIDI clock at 24 PPQN along with start, stop, continue and
+    /// song position messages, so other gear can follow this sequencer.
+    pub fn set_midi_clock_out(&mut self, enabled: bool) {
+        self.midi_out.set_clock_enabled(enabled);
+    }
+
+    /// Sends a Note On when the gate of the output rises and a Note Off when
+    /// it falls. A sounding note is released when the mapping changes.
+    pub fn set_midi_note(&mut self, index: usize, note: Option<MidiNote>) {
+        if let Option::Some(previous) = &self.midi_notes[index] {
+            if self.output_states[index].on {
+                self.midi_out.note(previous, false, false);
+            }
+        }
+
+        self.midi_notes[index] = note;
+    }
+
+    pub fn set_envelope(&mut self, index: usize, envelope: Envelope) {
+        self.outputs[index].set_envelope(envelope);
+    }
+
+    pub fn set_logic(&mut self, index: usize, logic: Logic) {
+        self.outputs[index].set_logic(logic);
     }
 
     pub fn set_prob(&mut self, index: usize, prob: Prob) {
@@ -84,8 +766,77 @@ impl Seq {
         self.outputs[index].set_pwm(&self.tick, pwm);
     }
 
+    /// Snaps the notes of an output to a scale.
+    pub fn set_quantizer(&mut self, index: usize, quantizer: Quantizer) {
+        self.outputs[index].set_quantizer(quantizer);
+    }
+
+    /// Changes the rate of an output when the `RateAlign` set with
+    /// `set_rate_align` allows. Aligned changes start a step on the boundary
+    /// they wait for and the sequence carries on from the next index.
     pub fn set_rate(&mut self, index: usize, rate: Rate) {
-        self.outputs[index].set_rate(&self.tick, rate);
+        let count = self.tick.count;
+        let at = match self.rate_align {
+            RateAlign::Immediate => {
+                self.outputs[index].set_rate(&self.tick, rate);
+                self.pending_rates[index] = Option::None;
+                return
+            }
+            RateAlign::Step => self.outputs[index].next_step(count),
+            RateAlign::Beat => count.next_multiple_of(self.resolution),
+            RateAlign::Bar => count.next_multiple_of(self.resolution * BEATS_PER_BAR),
+        };
+
+        self.pending_rates[index] = Option::Some((rate, at));
+    }
+
+    pub fn set_rate_align(&mut self, align: RateAlign) {
+        self.rate_align = align;
+    }
+
+    pub fn set_ratchet(&mut self, index: usize, step: usize, ratchet: Ratchet) {
+        self.outputs[index].set_ratchet(step, ratchet);
+    }
+
+    pub fn set_note(&mut self, index: usize, step: usize, note: Note) {
+        self.outputs[index].set_note(step, note);
+    }
+
+    pub fn set_tie(&mut self, index: usize, step: usize, tie: bool) {
+        self.outputs[index].set_tie(step, tie);
+    }
+
+    pub fn set_rotation(&mut self, index: usize, rotation: Rotation) {
+        self.outputs[index].set_rotation(rotation);
+    }
+
+    pub fn set_swing(&mut self, index: usize, swing: Swing) {
+        self.outputs[index].set_swing(&self.tick, swing);
+    }
+
+    /// Reseeds the random decisions of every output, e.g. from ADC noise at
+    /// power up. Each output draws from its own stream of the seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        for index in 0..self.output_states.len() {
+            self.set_output_seed(index, seed);
+        }
+    }
+
+    pub fn set_output_seed(&mut self, index: usize, seed: u64) {
+        let rng = &mut self.output_states[index].rng;
+        let locked = rng.is_locked();
+        *rng = Rng::with_seed(seed, index as u64);
+        rng.set_locked(locked);
+    }
+
+    /// Locks the random decisions of an output so they repeat on every loop
+    /// of its sequence.
+    pub fn set_random_lock(&mut self, index: usize, locked: bool) {
+        self.output_states[index].rng.set_locked(locked);
+    }
+
+    pub fn set_waveform(&mut self, index: usize, waveform: Waveform) {
+        self.outputs[index].set_waveform(waveform);
     }
 
     pub fn set_sequence(&mut self, index: usize, length: Length, density: Density) {
@@ -100,27 +851,34 @@ impl Seq {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{clock::Ppqn, midi::MIDI_PPQN};
 
     #[test]
     fn it_new() {
         let resolution = 1_920;
         let mut configs: Vec<Config, 4> = Vec::new();
         configs.resize_default(4).ok();
-        let seq = Seq::new_with_resolution(resolution, 120, configs);
-
-        let expected = OutputState {
-            index: 0,
-            index_change: false,
-            on: false,
-            on_change: false,
-            rng: Rng::new(),
-        };
+        let seq = Seq::new_with_resolution(resolution, Bpm::new(120), configs);
 
         assert_eq!(4, seq.output_states.len());
-        assert_eq!(expected, seq.output_states[0]);
-        assert_eq!(expected, seq.output_states[1]);
-        assert_eq!(expected, seq.output_states[2]);
-        assert_eq!(expected, seq.output_states[3]);
+
+        for (index, state) in seq.output_states.iter().enumerate() {
+            let expected = OutputState {
+                accent: false,
+                envelope: Default::default(),
+                index: 0,
+                index_change: false,
+                loop_register: 0,
+                note: Default::default(),
+                on: false,
+                on_change: false,
+                register: 0,
+                rng: Rng::with_seed(RNG_SEED, index as u64),
+                sample: 0,
+            };
+
+            assert_eq!(expected, *state);
+        }
     }
 
     #[test]
@@ -128,16 +886,22 @@ mod tests {
         let resolution = 2;
         let mut configs: Vec<Config, 4> = Vec::new();
         configs.resize_default(1).ok();
-        let mut seq = Seq::new_with_resolution(resolution, 120, configs);
+        let mut seq = Seq::new_with_resolution(resolution, Bpm::new(120), configs);
 
         seq.tick();
 
         let expected = OutputState {
+            accent: false,
+            envelope: Default::default(),
             index: 0,
             index_change: false,
+            loop_register: 0,
+            note: Default::default(),
             on: true,
             on_change: true,
-            rng: Rng::new(),
+            register: 0,
+            rng: Rng::with_seed(RNG_SEED, 0),
+            sample: 0,
         };
 
         assert_eq!(1, seq.output_states.len());
@@ -146,14 +910,1337 @@ mod tests {
         seq.tick();
 
         let expected = OutputState {
+            accent: false,
+            envelope: Default::default(),
             index: 0,
             index_change: false,
+            loop_register: 0,
+            note: Default::default(),
             on: false,
             on_change: true,
-            rng: Rng::new(),
+            register: 0,
+            rng: Rng::with_seed(RNG_SEED, 0),
+            sample: 0,
         };
 
         assert_eq!(1, seq.output_states.len());
         assert_eq!(expected, seq.output_states[0]);
     }
+
+    #[test]
+    fn it_outputs_the_cv_of_the_playing_step() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_output_type(0, OutputType::Pitch);
+        seq.set_note(0, 1, Note::new(2, 7));
+
+        seq.tick();
+        assert_eq!(0, seq.get_cv(0));
+
+        seq.skip(1_920);
+        assert_eq!(Note::new(2, 7), seq.get_note(0));
+        assert_eq!(2_583, seq.get_cv(0));
+    }
+
+    #[test]
+    fn it_quantizes_the_cv_of_the_playing_step() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_output_type(0, OutputType::Pitch);
+        seq.set_note(0, 0, Note::new(2, 6));
+        seq.set_quantizer(0, Quantizer::new(Scale::MinorPentatonic, 9));
+
+        seq.tick();
+
+        assert_eq!(Note::new(2, 7), seq.get_note(0));
+        assert_eq!(2_583, seq.get_cv(0));
+    }
+
+    #[test]
+    fn it_outputs_the_value_of_an_lfo_when_skipping() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Lfo);
+        configs[0].set_rate(Rate::Div(3, Frac::Zero));
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.get_value(0), scheduled.get_value(0));
+        }
+    }
+
+    #[test]
+    fn it_triggers_an_envelope_on_each_hit() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Envelope);
+        configs[0].set_sequence(Length(4), Density(2));
+        configs[0].set_envelope(Envelope::Adsr {
+            attack: EnvelopeTime::StepPercent(10),
+            decay: EnvelopeTime::StepPercent(10),
+            sustain: 32_768,
+            release: EnvelopeTime::StepPercent(50),
+        });
+        let mut seq = Seq::new(120, configs);
+
+        seq.tick();
+        assert_eq!(0, seq.get_value(0));
+        seq.skip(192);
+        assert_eq!(u16::MAX, seq.get_value(0));
+        seq.skip(768);
+        assert_eq!(32_768, seq.get_value(0));
+        seq.skip(960);
+        assert_eq!(0, seq.get_value(0));
+
+        seq.skip(1_920);
+        assert!(seq.get_on(0));
+        assert_eq!(0, seq.get_value(0));
+    }
+
+    #[test]
+    fn it_retriggers_an_envelope_restarted_while_its_gate_is_high() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Envelope);
+        configs[0].set_sequence(Length(1), Density(1));
+        configs[0].set_envelope(Envelope::Adsr {
+            attack: EnvelopeTime::StepPercent(10),
+            decay: EnvelopeTime::StepPercent(10),
+            sustain: 32_768,
+            release: EnvelopeTime::StepPercent(50),
+        });
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_note(
+            0,
+            Option::Some(MidiNote {
+                channel: 0,
+                note: 36,
+                velocity: 100,
+                accent_velocity: 127,
+            }),
+        );
+        seq.skip(2_000);
+        assert!(seq.get_on(0));
+        drain_midi(&mut seq);
+
+        seq.start();
+        assert_eq!(vec![0x80, 36, 0], drain_midi(&mut seq));
+        seq.tick();
+        assert!(seq.get_on_change(0));
+        assert_eq!(vec![0x90, 36, 100], drain_midi(&mut seq));
+        seq.skip(192);
+        assert_eq!(u16::MAX, seq.get_value(0));
+
+        for _ in 0..1_920 + 192 {
+            seq.tick();
+        }
+        assert_eq!(32_768, seq.get_value(0));
+    }
+
+    #[test]
+    fn it_outputs_the_value_of_an_envelope_when_skipping() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Envelope);
+        configs[0].set_sequence(Length(5), Density(3));
+        configs[0].set_ratchet(2, Ratchet::R3);
+        configs[0].set_rate(Rate::Mult(2, Frac::Zero));
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.get_value(0), scheduled.get_value(0));
+        }
+    }
+
+    #[test]
+    fn it_loops_a_locked_turing_machine() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Turing);
+        configs[0].set_sequence(Length(4), Density(4));
+        configs[0].set_prob(Prob::P50);
+        let mut seq = Seq::new(120, configs);
+
+        seq.tick();
+        seq.skip(8 * 1_920 - 1);
+        seq.set_random_lock(0, true);
+
+        let mut steps = [(false, 0); 8];
+        for step in steps.iter_mut() {
+            seq.skip(1);
+            *step = (seq.get_on(0), seq.get_value(0));
+            seq.skip(1_919);
+        }
+
+        assert_eq!(steps[..4], steps[4..]);
+        assert!(steps.iter().any(|(on, _)| *on));
+        for (on, value) in steps {
+            assert_eq!(on, value & 0x101 == 0x101);
+        }
+    }
+
+    #[test]
+    fn it_keeps_a_locked_loop_when_moving_to_a_song_position() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Turing);
+        configs[0].set_sequence(Length(4), Density(4));
+        configs[0].set_prob(Prob::P50);
+        let mut seq = Seq::new(120, configs);
+
+        seq.tick();
+        seq.skip(8 * 1_920 - 1);
+        seq.set_random_lock(0, true);
+
+        let mut steps = [0; 4];
+        for step in steps.iter_mut() {
+            seq.skip(1);
+            *step = seq.get_value(0);
+            seq.skip(1_919);
+        }
+        seq.skip(1);
+
+        seq.stop();
+        seq.tick();
+        for byte in [0xF2, 24, 0] {
+            seq.receive_midi(byte, 0);
+        }
+        seq.resume();
+
+        for index in [2, 3, 0, 1] {
+            seq.skip(1);
+            assert_eq!(steps[index], seq.get_value(0));
+            seq.skip(1_919);
+        }
+    }
+
+    #[test]
+    fn it_reads_logic_outputs_after_moving_to_a_song_position() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[1].set_output_type(OutputType::Logic);
+        configs[1].set_logic(Logic::new(LogicOp::Not, 0, 0));
+        let mut seq = Seq::new(120, configs);
+
+        seq.stop();
+        seq.tick();
+        for byte in [0xF2, 4, 0] {
+            seq.receive_midi(byte, 0);
+        }
+
+        assert!(!seq.get_on(0));
+        assert!(seq.get_on(1));
+    }
+
+    #[test]
+    fn it_flips_an_unlocked_turing_machine() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Turing);
+        configs[0].set_sequence(Length(4), Density(4));
+        configs[0].set_prob(Prob::P100);
+        let mut seq = Seq::new(120, configs);
+
+        let mut ons = [false; 8];
+        for on in ons.iter_mut() {
+            seq.skip(1);
+            *on = seq.get_on(0);
+            seq.skip(1_919);
+        }
+
+        assert_eq!([true, true, true, true, false, false, false, false], ons);
+        assert_eq!(0, seq.get_value(0));
+    }
+
+    #[test]
+    fn it_outputs_the_value_of_a_turing_machine_when_skipping() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_output_type(OutputType::Turing);
+        configs[0].set_sequence(Length(16), Density(16));
+        configs[0].set_rate(Rate::Mult(4, Frac::Zero));
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.get_on(0), scheduled.get_on(0));
+            assert_eq!(ticked.get_value(0), scheduled.get_value(0));
+        }
+    }
+
+    fn logic_configs() -> Vec<Config, 4> {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(4).ok();
+        configs[1].set_rate(Rate::Mult(2, Frac::Zero));
+        configs[1].set_sequence(Length(5), Density(3));
+        configs[1].set_output_type(OutputType::Euclid);
+        configs[2].set_output_type(OutputType::Logic);
+        configs[2].set_logic(Logic::new(LogicOp::Xor, 0, 1));
+        configs[3].set_output_type(OutputType::Logic);
+        configs[3].set_logic(Logic::new(LogicOp::Not, 2, 0));
+        configs
+    }
+
+    #[test]
+    fn it_combines_gates_on_the_same_tick() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(4).ok();
+        configs[1].set_rate(Rate::Mult(2, Frac::Zero));
+        configs[2].set_output_type(OutputType::Logic);
+        configs[2].set_logic(Logic::new(LogicOp::Xor, 0, 1));
+        configs[3].set_output_type(OutputType::Logic);
+        configs[3].set_logic(Logic::new(LogicOp::Not, 2, 0));
+        let mut seq = Seq::new(120, configs);
+
+        seq.tick();
+        assert!(!seq.get_on(2));
+        assert!(seq.get_on(3));
+        assert!(seq.get_on_change(3));
+
+        seq.skip(480);
+        assert!(!seq.get_on(1));
+        assert!(seq.get_on(2));
+        assert!(seq.get_on_change(2));
+        assert!(!seq.get_on(3));
+        assert!(seq.get_on_change(3));
+
+        seq.skip(480);
+        assert!(seq.get_on(1));
+        assert!(seq.get_on(2));
+        assert!(!seq.get_on_change(2));
+
+        seq.skip(480);
+        assert!(!seq.get_on(2));
+        assert!(seq.get_on_change(2));
+    }
+
+    #[test]
+    fn it_reads_later_logic_outputs_as_off() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_output_type(OutputType::Logic);
+        configs[0].set_logic(Logic::new(LogicOp::Not, 1, 0));
+        configs[1].set_output_type(OutputType::Logic);
+        configs[1].set_logic(Logic::new(LogicOp::Not, 0, 1));
+        let mut seq = Seq::new(120, configs);
+
+        seq.skip(1_000);
+
+        assert!(seq.get_on(0));
+        assert!(!seq.get_on(1));
+        assert!(seq.next_event().unwrap().outputs.is_empty());
+    }
+
+    #[test]
+    fn it_reports_a_logic_output_changed_between_ticks() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.tick();
+        assert!(seq.get_on(0));
+
+        seq.set_output_type(1, OutputType::Logic);
+        seq.set_logic(1, Logic::new(LogicOp::Not, 0, 0));
+        let event = seq.next_event().unwrap();
+        assert_eq!(0, event.ticks);
+        assert_eq!(&[1], &event.outputs[..]);
+
+        seq.skip(event.ticks);
+        seq.tick();
+        assert!(!seq.get_on(1));
+        assert!(seq.get_on_change(1));
+    }
+
+    #[test]
+    fn it_reports_a_logic_output_changed_by_a_stop() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[1].set_output_type(OutputType::Logic);
+        configs[1].set_logic(Logic::new(LogicOp::Nor, 0, 0));
+        let mut seq = Seq::new(120, configs);
+        seq.tick();
+        assert!(!seq.get_on(1));
+
+        seq.stop();
+        seq.tick();
+        seq.resume();
+        let event = seq.next_event().unwrap();
+        assert_eq!(0, event.ticks);
+        assert_eq!(&[1], &event.outputs[..]);
+
+        seq.skip(event.ticks);
+        seq.tick();
+        assert!(seq.get_on(1));
+        assert!(seq.get_on_change(1));
+    }
+
+    #[test]
+    fn it_stays_bit_exact_with_logic_outputs() {
+        let mut ticked = Seq::new(120, logic_configs());
+        let mut scheduled = Seq::new(120, logic_configs());
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.output_states, scheduled.output_states);
+        }
+    }
+
+    #[test]
+    fn it_schedules_events_of_logic_outputs() {
+        let mut ticked = Seq::new(120, logic_configs());
+        let mut scheduled = Seq::new(120, logic_configs());
+
+        while scheduled.tick.count < 1_920 * 16 {
+            let event = scheduled.next_event().unwrap();
+
+            for _ in 0..event.ticks {
+                ticked.tick();
+                assert!(ticked.output_states.iter().all(|state| !state.on_change));
+            }
+            ticked.tick();
+            scheduled.skip(event.ticks);
+            scheduled.tick();
+
+            let changed: Vec<usize, 4> = (0..4)
+                .filter(|&index| ticked.get_on_change(index))
+                .collect();
+            assert_eq!(changed, event.outputs);
+            assert_eq!(ticked.output_states, scheduled.output_states);
+        }
+    }
+
+    #[test]
+    fn it_changes_the_rate_immediately_by_default() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.skip(101);
+
+        seq.set_rate(0, Rate::Div(3, Frac::Zero));
+
+        assert_eq!(Rate::Div(3, Frac::Zero), seq.preset().configs[0].rate());
+    }
+
+    #[test]
+    fn it_changes_the_rate_when_the_step_ends() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_rate_align(RateAlign::Step);
+        seq.skip(101);
+
+        seq.set_rate(0, Rate::Mult(2, Frac::Zero));
+        assert_eq!(Rate::Unity, seq.preset().configs[0].rate());
+
+        seq.skip(860);
+        assert!(!seq.get_on(0));
+        assert!(seq.get_on_change(0));
+
+        seq.skip(959);
+        assert_eq!(0, seq.get_index(0));
+        seq.tick();
+        assert_eq!(1, seq.get_index(0));
+        assert!(seq.get_on(0));
+        assert_eq!(Rate::Mult(2, Frac::Zero), seq.preset().configs[0].rate());
+
+        seq.skip(960);
+        assert_eq!(2, seq.get_index(0));
+        assert!(seq.get_index_change(0));
+    }
+
+    #[test]
+    fn it_starts_a_step_on_the_bar_after_a_rate_change() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_rate_align(RateAlign::Bar);
+        seq.skip(101);
+
+        seq.set_rate(0, Rate::Div(3, Frac::Zero));
+        seq.skip(7_579);
+        assert_eq!(3, seq.get_index(0));
+        assert!(!seq.get_on(0));
+
+        seq.tick();
+        assert_eq!(4, seq.get_index(0));
+        assert!(seq.get_on(0));
+        assert!(seq.get_on_change(0));
+
+        seq.skip(5_760);
+        assert_eq!(5, seq.get_index(0));
+        assert!(seq.get_index_change(0));
+    }
+
+    #[test]
+    fn it_starts_a_step_on_the_beat_after_a_rate_change() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_rate(Rate::Div(2, Frac::Zero));
+        let mut seq = Seq::new(120, configs);
+        seq.set_rate_align(RateAlign::Beat);
+        seq.skip(101);
+
+        seq.set_rate(0, Rate::Mult(3, Frac::Zero));
+        seq.skip(1_819);
+        assert!(seq.get_on(0));
+        assert!(!seq.get_index_change(0));
+
+        seq.tick();
+        assert_eq!(1, seq.get_index(0));
+        assert!(seq.get_index_change(0));
+        assert_eq!(seq.get_on(1), seq.get_on(0));
+    }
+
+    #[test]
+    fn it_restarts_on_the_beat_grid_when_rewound() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_rate_align(RateAlign::Step);
+        seq.skip(101);
+        seq.set_rate(0, Rate::Div(3, Frac::Zero));
+
+        seq.start();
+        seq.tick();
+
+        assert_eq!(Rate::Div(3, Frac::Zero), seq.preset().configs[0].rate());
+        assert_eq!(0, seq.get_index(0));
+        seq.skip(5_760);
+        assert_eq!(1, seq.get_index(0));
+        assert!(seq.get_index_change(0));
+    }
+
+    #[test]
+    fn it_stays_bit_exact_across_aligned_rate_changes() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+        let rates = [
+            (RateAlign::Step, Rate::Div(3, Frac::Zero)),
+            (RateAlign::Beat, Rate::Mult(3, Frac::OneHalf)),
+            (RateAlign::Bar, Rate::Mult(5, Frac::Zero)),
+            (RateAlign::Immediate, Rate::Unity),
+        ];
+
+        for (round, (align, rate)) in rates.iter().cycle().take(16).enumerate() {
+            ticked.set_rate_align(*align);
+            scheduled.set_rate_align(*align);
+            ticked.set_rate(round % 4, *rate);
+            scheduled.set_rate(round % 4, *rate);
+
+            let target = ticked.tick.count + 1_000 + round as u32 * 317;
+            while scheduled.tick.count < target {
+                let event = scheduled.next_event().unwrap();
+
+                for _ in 0..event.ticks {
+                    ticked.tick();
+                }
+                ticked.tick();
+                scheduled.skip(event.ticks);
+                scheduled.tick();
+
+                let changed: Vec<usize, 4> = (0..4)
+                    .filter(|&index| ticked.get_on_change(index))
+                    .collect();
+                assert_eq!(changed, event.outputs);
+                assert_eq!(ticked.output_states, scheduled.output_states);
+            }
+        }
+    }
+
+    #[test]
+    fn it_plays_a_polyrhythm_in_step_with_the_beat() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_sequence(Length(3), Density(3));
+        configs[0].set_rate(Rate::Ratio(3, 2));
+        configs[1].set_sequence(Length(2), Density(2));
+        let mut seq = Seq::new(120, configs);
+
+        for _ in 0..1_000 {
+            seq.skip(3_840);
+            assert!(seq.is_loop_starting(0));
+            assert!(seq.is_loop_starting(1));
+        }
+    }
+
+    #[test]
+    fn it_stays_bit_exact_with_ratio_rates() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_rate(Rate::Ratio(7, 8));
+        configs[0].set_swing(Swing::P62);
+        configs[0].set_ratchet(3, Ratchet::R3);
+        configs[1].set_rate(Rate::Ratio(5, 3));
+        configs[1].set_output_type(OutputType::Lfo);
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.output_states, scheduled.output_states);
+            assert_eq!(ticked.get_value(1), scheduled.get_value(1));
+        }
+    }
+
+    #[test]
+    fn it_stays_bit_exact_with_ratchets_on_ratios_of_a_tick_per_step() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_rate(Rate::Ratio(1_000, 1));
+        configs[0].set_swing(Swing::P75);
+        configs[0].set_ratchet(0, Ratchet::R4);
+        configs[0].set_ratchet(1, Ratchet::R4);
+        configs[1].set_rate(Rate::Ratio(RESOLUTION, 1));
+        configs[1].set_ratchet(0, Ratchet::R3);
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+
+        for ticks in (0..200).map(|n| n * 7 % 101) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.output_states, scheduled.output_states);
+        }
+    }
+
+    #[test]
+    fn it_runs_by_default() {
+        let seq = Seq::default();
+
+        assert_eq!(Transport::Running, seq.get_transport());
+        assert_eq!(Option::None, seq.get_transport_change());
+    }
+
+    #[test]
+    fn it_forces_gates_low_on_stop() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+
+        seq.tick();
+        assert!(seq.get_on(0));
+
+        seq.stop();
+        seq.tick();
+
+        assert_eq!(Transport::Stopped, seq.get_transport());
+        assert_eq!(
+            Option::Some(TransportEvent::Stop),
+            seq.get_transport_change()
+        );
+        assert!(!seq.get_on(0));
+        assert!(seq.get_on_change(0));
+        assert_eq!(1, seq.tick.count);
+
+        seq.tick();
+
+        assert_eq!(Option::None, seq.get_transport_change());
+        assert!(!seq.get_on_change(0));
+        assert_eq!(1, seq.tick.count);
+    }
+
+    #[test]
+    fn it_forces_gates_low_when_reset_follows_stop_before_a_tick() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_clock_out(true);
+
+        seq.tick();
+        assert!(seq.get_on(0));
+        drain_midi(&mut seq);
+
+        seq.stop();
+        seq.reset();
+        seq.tick();
+
+        assert_eq!(Transport::Stopped, seq.get_transport());
+        assert!(!seq.get_on(0));
+        assert!(seq.get_on_change(0));
+        assert_eq!(vec![0xFC, 0xF2, 0, 0], drain_midi(&mut seq));
+    }
+
+    #[test]
+    fn it_continues_from_the_paused_position() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+
+        for _ in 0..1_921 {
+            seq.tick();
+        }
+        seq.pause();
+        seq.tick();
+        seq.tick();
+
+        assert_eq!(Transport::Paused, seq.get_transport());
+        assert_eq!(1_921, seq.tick.count);
+
+        seq.resume();
+        seq.tick();
+
+        assert_eq!(
+            Option::Some(TransportEvent::Continue),
+            seq.get_transport_change()
+        );
+        assert_eq!(1_922, seq.tick.count);
+        assert_eq!(1, seq.get_index(0));
+    }
+
+    #[test]
+    fn it_rewinds_to_the_first_step_on_start() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+
+        for _ in 0..(1_920 * 3 + 1) {
+            seq.tick();
+        }
+        assert_eq!(3, seq.get_index(0));
+
+        seq.stop();
+        seq.tick();
+        seq.start();
+        seq.tick();
+
+        assert_eq!(Transport::Running, seq.get_transport());
+        assert_eq!(
+            Option::Some(TransportEvent::Start),
+            seq.get_transport_change()
+        );
+        assert_eq!(1, seq.tick.count);
+        assert_eq!(0, seq.get_index(0));
+        assert!(seq.get_on(0));
+        assert!(seq.get_index_change(0));
+    }
+
+    #[test]
+    fn it_resets_while_running() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+
+        for _ in 0..(1_920 * 2 + 1) {
+            seq.tick();
+        }
+        seq.reset();
+        seq.tick();
+
+        assert_eq!(Transport::Running, seq.get_transport());
+        assert_eq!(
+            Option::Some(TransportEvent::Reset),
+            seq.get_transport_change()
+        );
+        assert_eq!(1, seq.tick.count);
+        assert_eq!(0, seq.get_index(0));
+    }
+
+    #[test]
+    fn it_sequences_eight_outputs_of_thirty_two_steps() {
+        let mut configs: Vec<ConfigN<32>, 8> = Vec::new();
+        configs.resize_default(8).ok();
+        let mut seq = SeqN::new(120, configs);
+        seq.set_output_type(7, OutputType::Euclid);
+        seq.set_sequence(7, Length(32), Density(5));
+
+        for _ in 0..(1_920 * 31 + 1) {
+            seq.tick();
+        }
+
+        assert_eq!(8, seq.output_states.len());
+        assert_eq!(31, seq.get_index(0));
+        assert_eq!(31, seq.get_index(7));
+        assert!(seq.get_on(0));
+        assert!(!seq.get_on(7));
+    }
+
+    #[test]
+    fn it_skips_different_steps_on_each_output_at_the_same_prob() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_prob(0, Prob::P50);
+        seq.set_prob(1, Prob::P50);
+
+        let mut ons: [[bool; 2]; 16] = Default::default();
+        for on in ons.iter_mut() {
+            seq.tick();
+            *on = [seq.get_on(0), seq.get_on(1)];
+            seq.skip(1_919);
+        }
+
+        assert!(ons.iter().any(|on| on[0] != on[1]));
+    }
+
+    #[test]
+    fn it_reseeds_random_decisions() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut first = Seq::new(120, configs.clone());
+        let mut second = Seq::new(120, configs);
+        first.set_prob(0, Prob::P50);
+        second.set_prob(0, Prob::P50);
+        second.set_seed(0xdead_beef);
+
+        let mut ons: [[bool; 2]; 16] = Default::default();
+        for on in ons.iter_mut() {
+            first.tick();
+            second.tick();
+            *on = [first.get_on(0), second.get_on(0)];
+            first.skip(1_919);
+            second.skip(1_919);
+        }
+
+        assert!(ons.iter().any(|on| on[0] != on[1]));
+    }
+
+    #[test]
+    fn it_saves_and_loads_a_preset() {
+        let mut seq = Seq::new(120, scheduled_configs());
+        let preset = seq.preset();
+
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(4).ok();
+        let mut loaded = Seq::new(90, configs);
+        loaded.load_preset(&preset);
+
+        assert_eq!(120, loaded.bpm());
+        assert_eq!(seq.outputs, loaded.outputs);
+
+        for _ in 0..1_920 * 8 {
+            seq.tick();
+            loaded.tick();
+        }
+
+        assert_eq!(seq.output_states, loaded.output_states);
+    }
+
+    fn scheduled_configs() -> Vec<Config, 4> {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(4).ok();
+        configs[0].set_swing(Swing::P66);
+        configs[0].set_ratchet(2, Ratchet::R3);
+        configs[1].set_output_type(OutputType::Euclid);
+        configs[1].set_sequence(Length(13), Density(5));
+        configs[1].set_rate(Rate::Mult(3, Frac::Zero));
+        configs[1].set_swing(Swing::P62);
+        configs[2].set_prob(Prob::P50);
+        configs[2].set_pwm(Pwm::P20);
+        configs[2].set_rate(Rate::Div(2, Frac::OneThird));
+        configs[3].set_output_type(OutputType::Euclid);
+        configs[3].set_sequence(Length(16), Density(0));
+        configs
+    }
+
+    #[test]
+    fn it_computes_the_next_event() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_rate(1, Rate::Mult(2, Frac::Zero));
+
+        let event = seq.next_event().unwrap();
+        assert_eq!(0, event.ticks);
+        assert_eq!(&[0, 1], &event.outputs[..]);
+
+        seq.tick();
+
+        let event = seq.next_event().unwrap();
+        assert_eq!(479, event.ticks);
+        assert_eq!(&[1], &event.outputs[..]);
+
+        seq.skip(event.ticks);
+        seq.tick();
+
+        assert!(!seq.get_on(1));
+        assert!(seq.get_on_change(1));
+        assert_eq!(481, seq.tick.count);
+    }
+
+    #[test]
+    fn it_has_no_events_while_stopped() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.stop();
+        seq.tick();
+
+        assert_eq!(Option::None, seq.next_event());
+    }
+
+    #[test]
+    fn it_stays_bit_exact_when_skipping_to_events() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+
+        while scheduled.tick.count < 1_920 * 64 {
+            let event = scheduled.next_event().unwrap();
+
+            for _ in 0..event.ticks {
+                ticked.tick();
+            }
+            ticked.tick();
+            scheduled.skip(event.ticks);
+            scheduled.tick();
+
+            assert_eq!(ticked.tick.count, scheduled.tick.count);
+            assert_eq!(ticked.output_states, scheduled.output_states);
+            assert_eq!(ticked.outputs, scheduled.outputs);
+            for index in event.outputs {
+                assert!(scheduled.get_on_change(index));
+            }
+        }
+    }
+
+    #[test]
+    fn it_stays_bit_exact_when_the_off_target_shrinks_while_a_gate_is_high() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut ticked = Seq::new(120, configs.clone());
+        let mut scheduled = Seq::new(120, configs);
+        for _ in 0..500 {
+            ticked.tick();
+            scheduled.tick();
+        }
+        ticked.set_pwm(0, Pwm::P10);
+        scheduled.set_pwm(0, Pwm::P10);
+
+        let event = scheduled.next_event().unwrap();
+        assert_eq!(76, event.ticks);
+        assert_eq!(&[0], &event.outputs[..]);
+
+        for _ in 0..event.ticks {
+            ticked.tick();
+        }
+        ticked.tick();
+        scheduled.skip(event.ticks);
+        scheduled.tick();
+
+        assert!(!scheduled.get_on(0));
+        assert!(ticked.get_on_change(0));
+        assert_eq!(ticked.output_states, scheduled.output_states);
+    }
+
+    #[test]
+    fn it_stays_bit_exact_when_skipping_arbitrary_ticks() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+
+        for ticks in (0..2_000).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(ticked.tick.count, scheduled.tick.count);
+            assert_eq!(ticked.output_states, scheduled.output_states);
+            assert_eq!(ticked.outputs, scheduled.outputs);
+        }
+    }
+
+    fn drain_midi<const OUTPUTS: usize, const STEPS: usize>(
+        seq: &mut SeqN<OUTPUTS, STEPS>,
+    ) -> std::vec::Vec<u8> {
+        core::iter::from_fn(|| seq.read_midi()).collect()
+    }
+
+    #[test]
+    fn it_emits_midi_clock_and_transport() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_clock_out(true);
+
+        for _ in 0..160 {
+            seq.tick();
+        }
+        assert_eq!(vec![0xF8, 0xF8], drain_midi(&mut seq));
+
+        seq.stop();
+        seq.tick();
+        assert_eq!(vec![0xFC], drain_midi(&mut seq));
+
+        seq.resume();
+        seq.tick();
+        assert_eq!(vec![0xF2, 0, 0, 0xFB, 0xF8], drain_midi(&mut seq));
+
+        seq.start();
+        seq.tick();
+        assert_eq!(vec![0xFA, 0xF8], drain_midi(&mut seq));
+    }
+
+    #[test]
+    fn it_sends_midi_notes_on_gate_edges() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(2).ok();
+        configs[0].set_accent(Length(2), Density(1), Rotation(0));
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_note(
+            0,
+            Option::Some(MidiNote {
+                channel: 9,
+                note: 36,
+                velocity: 100,
+                accent_velocity: 127,
+            }),
+        );
+
+        seq.tick();
+        assert_eq!(vec![0x99, 36, 127], drain_midi(&mut seq));
+
+        for _ in 1..1_920 {
+            seq.tick();
+        }
+        assert_eq!(vec![0x89, 36, 0], drain_midi(&mut seq));
+
+        seq.tick();
+        assert_eq!(vec![0x99, 36, 100], drain_midi(&mut seq));
+
+        seq.stop();
+        seq.tick();
+        assert_eq!(vec![0x89, 36, 0], drain_midi(&mut seq));
+    }
+
+    #[test]
+    fn it_ends_notes_when_start_follows_stop_before_a_tick() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_note(
+            0,
+            Option::Some(MidiNote {
+                channel: 9,
+                note: 36,
+                velocity: 100,
+                accent_velocity: 127,
+            }),
+        );
+
+        seq.tick();
+        assert_eq!(vec![0x99, 36, 100], drain_midi(&mut seq));
+
+        seq.stop();
+        seq.start();
+        seq.tick();
+        assert_eq!(vec![0x89, 36, 0, 0x99, 36, 100], drain_midi(&mut seq));
+        assert!(seq.get_on(0));
+    }
+
+    #[test]
+    fn it_sends_the_same_midi_notes_when_skipping_to_events() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+        for index in 0..4 {
+            let note = MidiNote {
+                channel: 0,
+                note: 36 + index as u8,
+                velocity: 100,
+                accent_velocity: 127,
+            };
+            ticked.set_midi_note(index, Option::Some(note));
+            scheduled.set_midi_note(index, Option::Some(note));
+        }
+
+        while scheduled.tick.count < 1_920 * 16 {
+            let event = scheduled.next_event().unwrap();
+
+            for _ in 0..event.ticks {
+                ticked.tick();
+            }
+            ticked.tick();
+            scheduled.skip(event.ticks);
+            scheduled.tick();
+
+            assert_eq!(drain_midi(&mut ticked), drain_midi(&mut scheduled));
+        }
+    }
+
+    #[test]
+    fn it_emits_the_same_midi_clock_when_skipping() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+        ticked.set_midi_clock_out(true);
+        scheduled.set_midi_clock_out(true);
+
+        for ticks in (0..200).map(|n| n * 7 % 1_013) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(drain_midi(&mut ticked), drain_midi(&mut scheduled));
+        }
+    }
+
+    #[test]
+    fn it_sends_the_same_midi_when_skipping_arbitrary_ticks() {
+        let mut ticked = Seq::new(120, scheduled_configs());
+        let mut scheduled = Seq::new(120, scheduled_configs());
+        for seq in [&mut ticked, &mut scheduled] {
+            seq.set_midi_clock_out(true);
+            for index in 0..3 {
+                let note = MidiNote {
+                    channel: 0,
+                    note: 36 + index as u8,
+                    velocity: 100,
+                    accent_velocity: 127,
+                };
+                seq.set_midi_note(index, Option::Some(note));
+            }
+        }
+
+        for ticks in (0..2_000).map(|n| n * 7 % 311) {
+            for _ in 0..ticks {
+                ticked.tick();
+            }
+            scheduled.skip(ticks);
+
+            assert_eq!(drain_midi(&mut ticked), drain_midi(&mut scheduled));
+        }
+    }
+
+    #[test]
+    fn it_schedules_an_event_for_each_midi_clock() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_midi_clock_out(true);
+        seq.tick();
+
+        assert_eq!(79, seq.next_event().unwrap().ticks);
+    }
+
+    #[test]
+    fn it_follows_midi_clock_and_transport() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(MIDI_PPQN)));
+        seq.stop();
+        seq.tick();
+
+        seq.receive_midi(0xFA, 0);
+        seq.tick();
+        assert_eq!(
+            Option::Some(TransportEvent::Start),
+            seq.get_transport_change()
+        );
+        assert_eq!(0, seq.tick.count);
+
+        for pulse in 0..24 {
+            seq.receive_midi(0xF8, pulse * 10_000);
+        }
+        assert_eq!(250, seq.bpm());
+        assert_eq!(1_841, seq.tick.count);
+
+        seq.receive_midi(0xFC, 240_000);
+        seq.tick();
+        assert_eq!(Transport::Stopped, seq.get_transport());
+    }
+
+    #[test]
+    fn it_moves_to_a_midi_song_position_while_stopped() {
+        let mut located = Seq::new(120, scheduled_configs());
+        let mut played = Seq::new(120, scheduled_configs());
+        for _ in 0..1_920 * 5 {
+            played.tick();
+        }
+        played.stop();
+        played.tick();
+
+        located.stop();
+        located.tick();
+        for byte in [0x90, 0x3C, 0xF2, 0x14, 0x00] {
+            located.receive_midi(byte, 0);
+        }
+
+        assert_eq!(played.tick.count, located.tick.count);
+        assert_eq!(played.outputs, located.outputs);
+        for index in 0..4 {
+            assert_eq!(played.get_index(index), located.get_index(index));
+            assert!(!located.get_on(index));
+        }
+    }
+
+    #[test]
+    fn it_holds_ticks_until_the_first_external_pulse() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(24)));
+
+        seq.tick();
+        seq.tick();
+        assert_eq!(0, seq.tick.count);
+        assert!(!seq.get_on(0));
+
+        seq.clock(0);
+        assert_eq!(1, seq.tick.count);
+        assert!(seq.get_on(0));
+        assert!(seq.get_on_change(0));
+
+        seq.tick();
+        assert_eq!(2, seq.tick.count);
+        assert!(!seq.get_on_change(0));
+    }
+
+    #[test]
+    fn it_follows_the_tempo_of_an_external_clock() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(60, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(24)));
+
+        seq.clock(0);
+        for _ in 0..79 {
+            seq.tick();
+        }
+        seq.clock(20_833);
+
+        assert_eq!(120, seq.bpm());
+        assert_eq!(260, seq.tick_duration_micros());
+        assert_eq!(81, seq.tick.count);
+    }
+
+    #[test]
+    fn it_times_ticks_from_the_start() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.skip(3 * 1_920);
+
+        assert_eq!(1_500_000, seq.tick_micros(seq.count()));
+
+        seq.start();
+        seq.tick();
+        assert_eq!(0, seq.tick_micros(0));
+        assert_eq!(500_000, seq.tick_micros(1_920));
+    }
+
+    #[test]
+    fn it_catches_up_when_an_external_clock_is_ahead() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(1)));
+
+        seq.clock(0);
+        for _ in 0..100 {
+            seq.tick();
+        }
+        seq.clock(250_000);
+
+        assert_eq!(1_921, seq.tick.count);
+        assert!(seq.get_on(0));
+        assert!(!seq.get_on_change(0));
+        assert!(seq.get_index_change(0));
+        assert_eq!(1, seq.get_index(0));
+    }
+
+    #[test]
+    fn it_sends_the_notes_of_every_edge_caught_up() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        configs[0].set_ratchet(0, Ratchet::R4);
+        let mut seq = Seq::new(120, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(1)));
+        seq.set_midi_note(
+            0,
+            Option::Some(MidiNote {
+                channel: 0,
+                note: 36,
+                velocity: 100,
+                accent_velocity: 127,
+            }),
+        );
+
+        seq.clock(0);
+        for _ in 0..100 {
+            seq.tick();
+        }
+        assert_eq!(vec![0x90, 36, 100], drain_midi(&mut seq));
+
+        seq.clock(250_000);
+
+        let expected: std::vec::Vec<u8> = [[0x80, 36, 0], [0x90, 36, 100]]
+            .iter()
+            .cycle()
+            .take(8)
+            .flatten()
+            .copied()
+            .collect();
+        assert_eq!(expected, drain_midi(&mut seq));
+    }
+
+    #[test]
+    fn it_holds_when_an_external_clock_is_behind() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(120, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(24)));
+
+        seq.clock(0);
+        for _ in 0..100 {
+            seq.tick();
+        }
+
+        assert_eq!(80, seq.tick.count);
+        assert!(!seq.is_clock_dropped());
+    }
+
+    #[test]
+    fn it_free_runs_at_the_last_tempo_when_an_external_clock_drops_out() {
+        let mut configs: Vec<Config, 4> = Vec::new();
+        configs.resize_default(1).ok();
+        let mut seq = Seq::new(60, configs);
+        seq.set_clock_source(ClockSource::External(Ppqn(24)));
+
+        seq.clock(0);
+        for _ in 0..79 {
+            seq.tick();
+        }
+        seq.clock(20_833);
+        for _ in 0..200 {
+            seq.tick();
+        }
+
+        assert!(seq.is_clock_dropped());
+        assert_eq!(120, seq.bpm());
+        assert_eq!(201, seq.tick.count);
+
+        seq.clock(1_000_000);
+
+        assert!(!seq.is_clock_dropped());
+        assert_eq!(120, seq.bpm());
+        assert_eq!(241, seq.tick.count);
+    }
 }
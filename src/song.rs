@@ -0,0 +1,347 @@
+use heapless::Vec;
+
+use super::{
+    output::{OutputType, MAX_OUTPUTS},
+    preset::PresetBank,
+    seq::SeqN,
+};
+
+pub const MAX_SONG_ENTRIES: usize = 16;
+
+/// Plays the scene of a `PresetBank` for a number of loops.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SongEntry {
+    pub scene: usize,
+    pub repeats: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SongPosition {
+    pub entry: usize,
+    pub repeat: u32,
+}
+
+/// A song of the default size, see `SongN`.
+pub type Song = SongN<MAX_SONG_ENTRIES, MAX_OUTPUTS>;
+
+/// Chains scenes into an arrangement.
+///
+/// Loops are counted on the first output that isn't a logic output. Each
+/// output switches to the pattern of the next entry when its own sequence
+/// wraps, so outputs of different lengths finish their loop before
+/// changing, while logic outputs switch straight away. The song starts
+/// over after the last entry and whenever the `Seq` is rewound.
+pub struct SongN<const ENTRIES: usize, const OUTPUTS: usize> {
+    entries: Vec<SongEntry, ENTRIES>,
+    last_count: Option<u32>,
+    loaded: Vec<Option<usize>, OUTPUTS>,
+    position: SongPosition,
+}
+
+impl<const ENTRIES: usize, const OUTPUTS: usize> Default for SongN<ENTRIES, OUTPUTS> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<const ENTRIES: usize, const OUTPUTS: usize> SongN<ENTRIES, OUTPUTS> {
+    pub fn new(entries: Vec<SongEntry, ENTRIES>) -> Self {
+        let mut loaded = Vec::new();
+        loaded.resize(OUTPUTS, Option::None).ok();
+
+        Self {
+            entries,
+            last_count: Option::None,
+            loaded,
+            position: Default::default(),
+        }
+    }
+
+    pub fn entries(&self) -> &[SongEntry] {
+        &self.entries
+    }
+
+    pub fn entry(&self) -> Option<&SongEntry> {
+        self.entries.get(self.position.entry)
+    }
+
+    pub fn position(&self) -> SongPosition {
+        self.position
+    }
+
+    /// Starts over from the first entry on the next loop of each output.
+    pub fn rewind(&mut self) {
+        self.position = Default::default();
+        for loaded in self.loaded.iter_mut() {
+            *loaded = Option::None;
+        }
+    }
+
+    /// Call before every `Seq::tick`. Advances the song when the counted
+    /// output wraps and loads the pattern of the current entry into every
+    /// output starting its sequence.
+    ///
+    /// While there are entries `Seq::next_event` also stops at every loop
+    /// boundary, so skipping to events never passes one.
+    pub fn tick<const SCENES: usize, const STEPS: usize>(
+        &mut self,
+        bank: &PresetBank<SCENES, OUTPUTS, STEPS>,
+        seq: &mut SeqN<OUTPUTS, STEPS>,
+    ) {
+        let count = seq.count();
+
+        match self.last_count {
+            Option::Some(last) if last == count => return,
+            Option::Some(last) if last < count => {}
+            _ => self.rewind(),
+        }
+        self.last_count = Option::Some(count);
+
+        seq.set_loop_events(!self.entries.is_empty());
+        if self.entries.is_empty() {
+            return
+        }
+
+        let counted =
+            (0..seq.output_count()).find(|&index| seq.get_output_type(index) != OutputType::Logic);
+        if let Option::Some(index) = counted {
+            if self.loaded[index].is_some() && seq.is_loop_starting(index) {
+                self.advance();
+            }
+        }
+
+        let entry = self.entries[self.position.entry];
+        let preset = match bank.scene(entry.scene) {
+            Option::Some(preset) => preset,
+            Option::None => return,
+        };
+
+        for (index, config) in preset.configs.iter().enumerate() {
+            if self.loaded[index] != Option::Some(self.position.entry)
+                && (seq.get_output_type(index) == OutputType::Logic || seq.is_loop_starting(index))
+            {
+                seq.load_pattern(index, config.clone());
+                self.loaded[index] = Option::Some(self.position.entry);
+            }
+        }
+    }
+
+    fn advance(&mut self) {
+        let repeats = self.entries[self.position.entry].repeats.max(1);
+
+        self.position.repeat += 1;
+        if self.position.repeat >= repeats {
+            self.position = SongPosition {
+                entry: (self.position.entry + 1) % self.entries.len(),
+                repeat: 0,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{ConfigN, Density, Length};
+
+    const BEAT: u32 = 1_920;
+
+    fn setup() -> (PresetBank<2, 2, 8>, SeqN<2, 8>) {
+        let mut configs: Vec<ConfigN<8>, 2> = Vec::new();
+        configs.resize_default(2).ok();
+        let mut seq = SeqN::new(120, configs);
+        let mut bank = PresetBank::new();
+
+        seq.set_sequence(0, Length(2), Density(2));
+        seq.set_sequence(1, Length(3), Density(3));
+        bank.save_scene(0, &seq);
+        seq.set_sequence(0, Length(4), Density(4));
+        seq.set_sequence(1, Length(4), Density(4));
+        bank.save_scene(1, &seq);
+
+        (bank, seq)
+    }
+
+    fn entries() -> Vec<SongEntry, 4> {
+        Vec::from_slice(&[
+            SongEntry {
+                scene: 0,
+                repeats: 2,
+            },
+            SongEntry {
+                scene: 1,
+                repeats: 1,
+            },
+        ])
+        .unwrap()
+    }
+
+    fn run<const ENTRIES: usize>(
+        song: &mut SongN<ENTRIES, 2>,
+        bank: &PresetBank<2, 2, 8>,
+        seq: &mut SeqN<2, 8>,
+        ticks: u32,
+    ) {
+        for _ in 0..ticks {
+            song.tick(bank, seq);
+            seq.tick();
+        }
+    }
+
+    #[test]
+    fn it_loads_the_first_entry_on_start() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(entries());
+
+        run(&mut song, &bank, &mut seq, 1);
+
+        assert_eq!(bank.scene(0), Option::Some(&seq.preset()));
+        assert_eq!(SongPosition::default(), song.position());
+    }
+
+    #[test]
+    fn it_advances_after_the_repeats_of_an_entry() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(entries());
+
+        run(&mut song, &bank, &mut seq, 2 * BEAT + 1);
+        assert_eq!(
+            SongPosition {
+                entry: 0,
+                repeat: 1
+            },
+            song.position()
+        );
+
+        run(&mut song, &bank, &mut seq, 2 * BEAT);
+        assert_eq!(
+            SongPosition {
+                entry: 1,
+                repeat: 0
+            },
+            song.position()
+        );
+        assert_eq!(0, seq.get_index(0));
+        assert_eq!(bank.scene(1).unwrap().configs[0], seq.preset().configs[0]);
+    }
+
+    #[test]
+    fn it_switches_each_output_when_its_loop_ends() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(entries());
+
+        run(&mut song, &bank, &mut seq, 4 * BEAT + 1);
+
+        assert_eq!(bank.scene(1).unwrap().configs[0], seq.preset().configs[0]);
+        assert_eq!(bank.scene(0).unwrap().configs[1], seq.preset().configs[1]);
+        assert_eq!(1, seq.get_index(1));
+
+        run(&mut song, &bank, &mut seq, 2 * BEAT);
+
+        assert_eq!(bank.scene(1).unwrap().configs[1], seq.preset().configs[1]);
+        assert_eq!(0, seq.get_index(1));
+    }
+
+    #[test]
+    fn it_starts_over_after_the_last_entry() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(entries());
+
+        run(&mut song, &bank, &mut seq, 8 * BEAT + 1);
+
+        assert_eq!(SongPosition::default(), song.position());
+        assert_eq!(bank.scene(0).unwrap().configs[0], seq.preset().configs[0]);
+    }
+
+    #[test]
+    fn it_counts_loops_on_the_first_output_that_is_not_logic() {
+        let (mut bank, mut seq) = setup();
+        seq.set_output_type(0, OutputType::Logic);
+        seq.set_sequence(1, Length(3), Density(3));
+        bank.save_scene(0, &seq);
+        let mut song: SongN<4, 2> = SongN::new(entries());
+
+        run(&mut song, &bank, &mut seq, 3 * BEAT + 1);
+        assert_eq!(
+            SongPosition {
+                entry: 0,
+                repeat: 1
+            },
+            song.position()
+        );
+
+        run(&mut song, &bank, &mut seq, 3 * BEAT);
+        assert_eq!(1, song.position().entry);
+        assert_eq!(bank.scene(1).unwrap().configs[0], seq.preset().configs[0]);
+    }
+
+    #[test]
+    fn it_advances_the_same_when_skipping_to_events() {
+        let (mut bank, mut ticked) = setup();
+        for scene in 0..2 {
+            let mut preset = bank.scene(scene).unwrap().clone();
+            for config in preset.configs.iter_mut() {
+                config.set_output_type(OutputType::Euclid);
+                config.set_sequence(config.length(), Density(0));
+            }
+            bank.set_scene(scene, preset);
+        }
+        let (_, mut scheduled) = setup();
+        let mut ticked_song: SongN<4, 2> = SongN::new(entries());
+        let mut scheduled_song: SongN<4, 2> = SongN::new(entries());
+
+        while scheduled.count() < 16 * BEAT {
+            scheduled_song.tick(&bank, &mut scheduled);
+            let event = scheduled.next_event().unwrap();
+
+            run(&mut ticked_song, &bank, &mut ticked, event.ticks + 1);
+            scheduled.skip(event.ticks);
+            scheduled_song.tick(&bank, &mut scheduled);
+            scheduled.tick();
+
+            assert_eq!(ticked_song.position(), scheduled_song.position());
+            assert_eq!(ticked.preset(), scheduled.preset());
+            assert_eq!(ticked.get_index(0), scheduled.get_index(0));
+            assert_eq!(ticked.get_index(1), scheduled.get_index(1));
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_pattern_on_an_entry_of_a_missing_scene() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(
+            Vec::from_slice(&[
+                SongEntry {
+                    scene: 5,
+                    repeats: 1,
+                },
+                SongEntry {
+                    scene: 0,
+                    repeats: 1,
+                },
+            ])
+            .unwrap(),
+        );
+        let played = seq.preset();
+        assert_eq!(Option::None, bank.scene(5));
+
+        run(&mut song, &bank, &mut seq, 4 * BEAT + 1);
+
+        assert_eq!(played, seq.preset());
+        assert_eq!(0, song.position().entry);
+    }
+
+    #[test]
+    fn it_starts_over_when_the_seq_rewinds() {
+        let (bank, mut seq) = setup();
+        let mut song: SongN<4, 2> = SongN::new(entries());
+        run(&mut song, &bank, &mut seq, 4 * BEAT + 1);
+
+        seq.start();
+        run(&mut song, &bank, &mut seq, 1);
+
+        assert_eq!(SongPosition::default(), song.position());
+        assert_eq!(bank.scene(0), Option::Some(&seq.preset()));
+    }
+}
@@ -0,0 +1,198 @@
+use heapless::{Deque, Vec};
+
+use super::{
+    bpm::{Bpm, BPM_SCALE},
+    seq::SeqN,
+};
+
+pub const MAX_TAPS: usize = 8;
+
+/// Taps further apart than this start a new measurement.
+pub const TAP_TIMEOUT_MICROS: u64 = 2_000_000;
+
+/// Intervals further than this from the median, in percent, are ignored.
+const OUTLIER_PERCENT: u64 = 20;
+
+const MICRO_SECONDS_PER_MINUTE: u64 = 60_000_000;
+
+/// A tap tempo of the default size, see `TapTempoN`.
+pub type TapTempo = TapTempoN<MAX_TAPS>;
+
+/// Derives a tempo from taps on a button.
+///
+/// The last `TAPS` intervals between taps are kept and averaged, leaving
+/// out any far from their median, so a single early or late tap doesn't
+/// throw the tempo off and a new tempo takes over once most of the taps
+/// follow it. Pausing for longer than the timeout starts over.
+pub struct TapTempoN<const TAPS: usize> {
+    intervals: Deque<u64, TAPS>,
+    last: Option<u64>,
+    timeout_micros: u64,
+}
+
+impl<const TAPS: usize> Default for TapTempoN<TAPS> {
+    fn default() -> Self {
+        Self::new(TAP_TIMEOUT_MICROS)
+    }
+}
+
+impl<const TAPS: usize> TapTempoN<TAPS> {
+    pub fn new(timeout_micros: u64) -> Self {
+        Self {
+            intervals: Deque::new(),
+            last: Option::None,
+            timeout_micros,
+        }
+    }
+
+    /// Forgets all taps.
+    pub fn reset(&mut self) {
+        self.intervals.clear();
+        self.last = Option::None;
+    }
+
+    /// Registers a tap at `micros` and sets the tempo of `seq` from the
+    /// taps so far. Returns the tempo set, if any.
+    pub fn tap<const OUTPUTS: usize, const STEPS: usize>(
+        &mut self,
+        micros: u64,
+        seq: &mut SeqN<OUTPUTS, STEPS>,
+    ) -> Option<Bpm> {
+        let interval = self.last.map(|last| micros.wrapping_sub(last));
+
+        match interval {
+            Option::Some(0) => return Option::None,
+            Option::Some(interval) if interval <= self.timeout_micros => {
+                if self.intervals.is_full() {
+                    self.intervals.pop_front();
+                }
+                self.intervals.push_back(interval).ok();
+            }
+            _ => self.intervals.clear(),
+        }
+        self.last = Option::Some(micros);
+
+        let tempo = self.tempo()?;
+        seq.set_tempo(tempo);
+        Option::Some(tempo)
+    }
+
+    /// Tempo of the intervals tapped, once there are any.
+    pub fn tempo(&self) -> Option<Bpm> {
+        let mut sorted: Vec<u64, TAPS> = self.intervals.iter().copied().collect();
+        sorted.sort_unstable();
+        let median = *sorted.get(sorted.len() / 2)?;
+        let tolerance = median * OUTLIER_PERCENT / 100;
+
+        let (sum, len) = sorted
+            .iter()
+            .filter(|interval| interval.abs_diff(median) <= tolerance)
+            .fold((0, 0), |(sum, len), interval| (sum + interval, len + 1));
+
+        let micros_per_minute = MICRO_SECONDS_PER_MINUTE * BPM_SCALE as u64 * len;
+        let hundredths = (micros_per_minute + sum / 2) / sum;
+
+        let hundredths =
+            hundredths.clamp(Bpm::MIN.hundredths() as u64, Bpm::MAX.hundredths() as u64);
+
+        Option::Some(Bpm::from_hundredths(hundredths as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq::Seq;
+
+    fn tap_all(tap_tempo: &mut TapTempo, seq: &mut Seq, taps: &[u64]) -> Option<Bpm> {
+        taps.iter()
+            .map(|&micros| tap_tempo.tap(micros, seq))
+            .last()
+            .flatten()
+    }
+
+    #[test]
+    fn it_needs_two_taps() {
+        let mut tap_tempo = TapTempo::default();
+        let mut seq = Seq::default();
+
+        assert_eq!(Option::None, tap_tempo.tap(1_000, &mut seq));
+        assert_eq!(
+            Option::Some(Bpm::new(100)),
+            tap_tempo.tap(601_000, &mut seq)
+        );
+        assert_eq!(100, seq.bpm());
+    }
+
+    #[test]
+    fn it_averages_the_intervals() {
+        let mut tap_tempo = TapTempo::default();
+        let mut seq = Seq::default();
+
+        let tempo = tap_all(&mut tap_tempo, &mut seq, &[0, 489_000, 980_000, 1_469_388]);
+
+        assert_eq!(Option::Some(Bpm::from_hundredths(12_250)), tempo);
+        assert_eq!(Bpm::from_hundredths(12_250), seq.tempo());
+    }
+
+    #[test]
+    fn it_ignores_outliers() {
+        let mut tap_tempo = TapTempo::default();
+        let mut seq = Seq::default();
+
+        let tempo = tap_all(
+            &mut tap_tempo,
+            &mut seq,
+            &[0, 500_000, 1_000_000, 1_800_000, 2_300_000, 2_800_000],
+        );
+
+        assert_eq!(Option::Some(Bpm::new(120)), tempo);
+    }
+
+    #[test]
+    fn it_follows_a_new_tempo() {
+        let mut tap_tempo: TapTempoN<4> = Default::default();
+        let mut seq = Seq::default();
+
+        for tap in 0..5 {
+            tap_tempo.tap(tap * 500_000, &mut seq);
+        }
+        for tap in 1..4 {
+            tap_tempo.tap(2_000_000 + tap * 1_000_000, &mut seq);
+        }
+
+        assert_eq!(Option::Some(Bpm::new(60)), tap_tempo.tempo());
+    }
+
+    #[test]
+    fn it_clamps_the_tempo() {
+        let mut tap_tempo = TapTempo::default();
+        let mut seq = Seq::default();
+
+        assert_eq!(
+            Option::Some(Bpm::MAX),
+            tap_all(&mut tap_tempo, &mut seq, &[0, 1])
+        );
+        assert_eq!(Bpm::MAX, seq.tempo());
+
+        let mut tap_tempo = TapTempo::new(u64::MAX);
+        assert_eq!(
+            Option::Some(Bpm::MIN),
+            tap_all(&mut tap_tempo, &mut seq, &[0, 3_600_000_000])
+        );
+    }
+
+    #[test]
+    fn it_starts_over_after_a_timeout() {
+        let mut tap_tempo = TapTempo::default();
+        let mut seq = Seq::default();
+        tap_all(&mut tap_tempo, &mut seq, &[0, 500_000, 1_000_000]);
+
+        assert_eq!(Option::None, tap_tempo.tap(3_500_000, &mut seq));
+        assert_eq!(Option::None, tap_tempo.tempo());
+        assert_eq!(
+            Option::Some(Bpm::new(60)),
+            tap_tempo.tap(4_500_000, &mut seq)
+        );
+    }
+}
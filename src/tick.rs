@@ -1,15 +1,28 @@
-use super::math;
+use super::{
+    bpm::{Bpm, BPM_SCALE},
+    math,
+};
 
 const MAX_MULT: u32 = 192;
 const PWM_PERCENT_INCREMENTS: u32 = 10;
 const SECONDS_IN_MINUTES: f32 = 60.0;
 const MICRO_SECONDS_PER_SECOND: f32 = 1_000_000.0;
+const MICRO_SECONDS_PER_MINUTE: u64 = 60_000_000;
 pub const RESOLUTION: u32 = PWM_PERCENT_INCREMENTS * MAX_MULT;
 
 pub struct Tick {
+    /// `tempo` rounded to whole BPM.
     pub bpm: u32,
     pub count: u32,
+    /// Tick period rounded to whole microseconds. Use `micros_at` to
+    /// schedule ticks without drift.
     pub duration_micros: u64,
+    /// Exact tick period of `period_micros / period_ticks` microseconds.
+    period_micros: u64,
+    period_ticks: u64,
+    /// Count and time the schedule is laid out from.
+    origin: (u32, u64),
+    pub tempo: Bpm,
 }
 
 impl Default for Tick {
@@ -20,23 +33,102 @@ impl Default for Tick {
 
 impl Tick {
     pub fn new(bpm: u32) -> Self {
+        Self::with_tempo(Bpm::new(bpm))
+    }
+
+    pub fn with_tempo(tempo: Bpm) -> Self {
         let mut tick = Self {
             count: 0,
-            bpm,
+            bpm: tempo.whole(),
             duration_micros: 0,
+            period_micros: MICRO_SECONDS_PER_MINUTE,
+            period_ticks: 1,
+            origin: (0, 0),
+            tempo,
         };
 
-        tick.set_bpm(bpm);
+        tick.set_tempo(tempo);
         tick
     }
 
     pub fn set_bpm(&mut self, bpm: u32) {
-        self.bpm = bpm;
-        self.duration_micros = self.duration_micros(bpm);
+        self.set_tempo(Bpm::new(bpm));
     }
 
-    fn duration_micros(&self, bpm: u32) -> u64 {
-        let beats_per_minute = bpm as f32;
+    pub fn set_tempo(&mut self, tempo: Bpm) {
+        self.set_period(
+            MICRO_SECONDS_PER_MINUTE * BPM_SCALE as u64,
+            tempo.hundredths().max(1) as u64 * RESOLUTION as u64,
+        );
+        self.tempo = tempo;
+        self.bpm = tempo.whole();
+        self.duration_micros = self.duration_micros(tempo);
+    }
+
+    /// Derives bpm and tick duration from the interval between external
+    /// clock pulses, each of which spans `ticks_per_pulse` ticks.
+    pub fn set_pulse_interval(&mut self, interval_micros: u64, ticks_per_pulse: u32, ppqn: u32) {
+        let ticks_per_pulse = ticks_per_pulse as u64;
+        let micros_per_beat = interval_micros * ppqn as u64;
+
+        self.set_period(interval_micros, ticks_per_pulse);
+
+        let micros_per_minute = MICRO_SECONDS_PER_MINUTE * BPM_SCALE as u64;
+        self.tempo = Bpm::from_hundredths(
+            ((micros_per_minute + micros_per_beat / 2) / micros_per_beat) as u32,
+        );
+        self.bpm = self.tempo.whole();
+        self.duration_micros = (interval_micros + ticks_per_pulse / 2) / ticks_per_pulse;
+    }
+
+    /// Moves to `count`, laying the schedule out from it so `micros_at`
+    /// is 0 there.
+    pub fn set_count(&mut self, count: u32) {
+        self.count = count;
+        self.origin = (count, 0);
+    }
+
+    /// Time of the tick at `count` in microseconds, relative to the count
+    /// last set with `set_count`. The period is kept as an exact fraction
+    /// and each time is rounded down on its own, so tick durations vary by
+    /// a microsecond while their average is exact and never drifts. Tempo
+    /// changes take effect from the current count without moving earlier
+    /// ticks; counts before that are extrapolated at the current tempo.
+    pub fn micros_at(&self, count: u32) -> u64 {
+        let (origin_count, origin_micros) = self.origin;
+
+        if count >= origin_count {
+            origin_micros + self.span_micros(count - origin_count, false)
+        } else {
+            origin_micros.saturating_sub(self.span_micros(origin_count - count, true))
+        }
+    }
+
+    /// Time between the tick at `count` and the next.
+    pub fn duration_micros_at(&self, count: u32) -> u64 {
+        self.micros_at(count + 1) - self.micros_at(count)
+    }
+
+    fn span_micros(&self, ticks: u32, ceil: bool) -> u64 {
+        let micros = ticks as u128 * self.period_micros as u128;
+        let period_ticks = self.period_ticks as u128;
+        let span = if ceil {
+            micros.div_ceil(period_ticks)
+        } else {
+            micros / period_ticks
+        };
+
+        span as u64
+    }
+
+    fn set_period(&mut self, period_micros: u64, period_ticks: u64) {
+        self.origin = (self.count, self.micros_at(self.count));
+        self.period_micros = period_micros;
+        self.period_ticks = period_ticks.max(1);
+    }
+
+    fn duration_micros(&self, tempo: Bpm) -> u64 {
+        let beats_per_minute = tempo.hundredths() as f32 / BPM_SCALE as f32;
         let beats_per_second = beats_per_minute / SECONDS_IN_MINUTES;
         let ticks_per_second = beats_per_second * RESOLUTION as f32;
         math::round(MICRO_SECONDS_PER_SECOND / ticks_per_second) as u64
@@ -81,4 +173,101 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn it_computes_bpm_and_tick_duration_from_a_24_ppqn_pulse_interval() {
+        let mut tick = Tick::new(10);
+        tick.set_pulse_interval(20_833, 80, 24);
+
+        assert_eq!(120, tick.bpm);
+        assert_eq!(260, tick.duration_micros);
+    }
+
+    #[test]
+    fn it_computes_bpm_and_tick_duration_from_a_1_ppqn_pulse_interval() {
+        let mut tick = Tick::new(120);
+        tick.set_pulse_interval(600_000, 1_920, 1);
+
+        assert_eq!(100, tick.bpm);
+        assert_eq!(313, tick.duration_micros);
+    }
+
+    #[test]
+    fn it_computes_tick_duration_in_millis_for_a_fractional_bpm() {
+        let mut tick = Tick::new(120);
+        tick.set_tempo(Bpm::from_hundredths(12_250));
+
+        assert_eq!(123, tick.bpm);
+        assert_eq!(255, tick.duration_micros);
+        assert_eq!(60_000_000, tick.micros_at(12_250 * RESOLUTION / 100));
+    }
+
+    #[test]
+    fn it_starts_at_a_fractional_tempo() {
+        let tick = Tick::with_tempo(Bpm::from_hundredths(12_250));
+
+        assert_eq!(Bpm::from_hundredths(12_250), tick.tempo);
+        assert_eq!(123, tick.bpm);
+        assert_eq!(255, tick.duration_micros);
+    }
+
+    #[test]
+    fn it_computes_a_fractional_bpm_from_a_pulse_interval() {
+        let mut tick = Tick::new(120);
+        tick.set_pulse_interval(20_408, 80, 24);
+
+        assert_eq!(Bpm::from_hundredths(12_250), tick.tempo);
+    }
+
+    #[test]
+    fn it_schedules_ticks_without_drift() {
+        let tick = Tick::new(120);
+
+        assert_eq!(60_000_000, tick.micros_at(120 * RESOLUTION));
+        assert_eq!(3_600_000_000, tick.micros_at(60 * 120 * RESOLUTION));
+    }
+
+    #[test]
+    fn it_spreads_the_fraction_of_a_microsecond_over_ticks() {
+        let tick = Tick::new(120);
+        let durations: Vec<u64> = (0..12)
+            .map(|count| tick.duration_micros_at(count))
+            .collect();
+
+        assert_eq!(3_125, durations.iter().sum::<u64>());
+        assert!(durations
+            .iter()
+            .all(|&micros| micros == 260 || micros == 261));
+    }
+
+    #[test]
+    fn it_keeps_the_time_of_past_ticks_when_the_tempo_changes() {
+        let mut tick = Tick::new(120);
+        tick.count = 1_000;
+        let micros = tick.micros_at(1_000);
+
+        tick.set_bpm(60);
+
+        assert_eq!(micros, tick.micros_at(1_000));
+        assert_eq!(micros + 1_000_000, tick.micros_at(1_000 + RESOLUTION));
+    }
+
+    #[test]
+    fn it_schedules_ticks_of_an_external_clock() {
+        let mut tick = Tick::new(120);
+        tick.set_pulse_interval(20_833, 80, 24);
+
+        assert_eq!(20_833 * 24, tick.micros_at(80 * 24));
+    }
+
+    #[test]
+    fn it_lays_out_the_schedule_from_the_count_set() {
+        let mut tick = Tick::new(120);
+
+        tick.set_count(RESOLUTION);
+
+        assert_eq!(0, tick.micros_at(RESOLUTION));
+        assert_eq!(500_000, tick.micros_at(2 * RESOLUTION));
+        assert_eq!(0, tick.micros_at(0));
+    }
 }
@@ -0,0 +1,105 @@
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Transport {
+    Stopped,
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransportEvent {
+    Start,
+    Stop,
+    Pause,
+    Continue,
+    Reset,
+}
+
+impl Transport {
+    pub fn is_running(&self) -> bool {
+        *self == Transport::Running
+    }
+
+    /// The state reached by applying `event`, or `None` if the event has no
+    /// effect in the current state.
+    pub fn next(&self, event: TransportEvent) -> Option<Transport> {
+        match (self, event) {
+            (_, TransportEvent::Start) => Option::Some(Transport::Running),
+            (Transport::Stopped, TransportEvent::Stop) => Option::None,
+            (_, TransportEvent::Stop) => Option::Some(Transport::Stopped),
+            (Transport::Running, TransportEvent::Pause) => Option::Some(Transport::Paused),
+            (_, TransportEvent::Pause) => Option::None,
+            (Transport::Running, TransportEvent::Continue) => Option::None,
+            (_, TransportEvent::Continue) => Option::Some(Transport::Running),
+            (&state, TransportEvent::Reset) => Option::Some(state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_starts_from_any_state() {
+        assert_eq!(
+            Option::Some(Transport::Running),
+            Transport::Stopped.next(TransportEvent::Start)
+        );
+        assert_eq!(
+            Option::Some(Transport::Running),
+            Transport::Paused.next(TransportEvent::Start)
+        );
+        assert_eq!(
+            Option::Some(Transport::Running),
+            Transport::Running.next(TransportEvent::Start)
+        );
+    }
+
+    #[test]
+    fn it_only_pauses_when_running() {
+        assert_eq!(
+            Option::Some(Transport::Paused),
+            Transport::Running.next(TransportEvent::Pause)
+        );
+        assert_eq!(Option::None, Transport::Stopped.next(TransportEvent::Pause));
+        assert_eq!(Option::None, Transport::Paused.next(TransportEvent::Pause));
+    }
+
+    #[test]
+    fn it_continues_when_stopped_or_paused() {
+        assert_eq!(
+            Option::Some(Transport::Running),
+            Transport::Stopped.next(TransportEvent::Continue)
+        );
+        assert_eq!(
+            Option::Some(Transport::Running),
+            Transport::Paused.next(TransportEvent::Continue)
+        );
+        assert_eq!(
+            Option::None,
+            Transport::Running.next(TransportEvent::Continue)
+        );
+    }
+
+    #[test]
+    fn it_stops_unless_stopped() {
+        assert_eq!(
+            Option::Some(Transport::Stopped),
+            Transport::Running.next(TransportEvent::Stop)
+        );
+        assert_eq!(
+            Option::Some(Transport::Stopped),
+            Transport::Paused.next(TransportEvent::Stop)
+        );
+        assert_eq!(Option::None, Transport::Stopped.next(TransportEvent::Stop));
+    }
+
+    #[test]
+    fn it_resets_without_changing_state() {
+        assert_eq!(
+            Option::Some(Transport::Paused),
+            Transport::Paused.next(TransportEvent::Reset)
+        );
+    }
+}